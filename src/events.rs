@@ -0,0 +1,71 @@
+use soroban_sdk::{symbol_short, Env, Symbol};
+
+use crate::types::{asset::Asset, asset_id::AssetId, config_data::ConfigData};
+
+// Publishes the price update event for the asset. The timestamp is in seconds.
+pub fn publish_price_update(e: &Env, asset: AssetId, price: i128, timestamp: u64) {
+    e.events().publish(
+        (
+            symbol_short!("oracle"),
+            Symbol::new(e, "price_update"),
+            asset.0 as u32,
+        ),
+        (price, timestamp),
+    );
+}
+
+// Reason code of the price update rejected by the price bounds check. Code 1 is reserved for max price change checks.
+pub const REJECTED_PRICE_OUT_OF_BOUNDS: u32 = 2;
+
+// Publishes the event for the price update rejected by the circuit breaker checks.
+pub fn publish_price_rejected(
+    e: &Env,
+    asset: AssetId,
+    new_price: i128,
+    old_price: i128,
+    reason_code: u32,
+) {
+    e.events().publish(
+        (
+            symbol_short!("oracle"),
+            Symbol::new(e, "rejected"),
+            asset.0 as u32,
+        ),
+        (new_price, old_price, reason_code),
+    );
+}
+
+// Publishes the event for the asset added to the quoted assets list.
+pub fn publish_asset_added(e: &Env, asset: Asset, index: u32) {
+    e.events().publish(
+        (symbol_short!("oracle"), Symbol::new(e, "asset_added")),
+        (asset, index),
+    );
+}
+
+// Publishes the contract configuration event.
+pub fn publish_config_updated(e: &Env, config: ConfigData) {
+    e.events().publish(
+        (symbol_short!("oracle"), Symbol::new(e, "config_updated")),
+        config,
+    );
+}
+
+// Publishes the retention period change event. The period is in milliseconds.
+pub fn publish_period_changed(e: &Env, period: u64) {
+    e.events().publish(
+        (symbol_short!("oracle"), Symbol::new(e, "period_changed")),
+        period,
+    );
+}
+
+// Publishes the base asset change event.
+pub fn publish_base_asset_updated(e: &Env, old_base: Asset, new_base: Asset) {
+    e.events().publish(
+        (
+            symbol_short!("oracle"),
+            Symbol::new(e, "base_asset_updated"),
+        ),
+        (old_base, new_base),
+    );
+}
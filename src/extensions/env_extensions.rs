@@ -1,29 +1,97 @@
 #![allow(non_upper_case_globals)]
-use soroban_sdk::storage::{Instance, Temporary};
-use soroban_sdk::{panic_with_error, Address, Env, Vec};
+use soroban_sdk::storage::{Instance, Persistent, Temporary};
+use soroban_sdk::{panic_with_error, Address, BytesN, Env, Map, Vec};
 
 use crate::extensions;
 use crate::types;
 
 use extensions::u128_helper::U128Helper;
-use types::{asset::Asset, error::Error};
+use types::{
+    asset::Asset, asset_id::AssetId, asset_metadata::AssetMetadata, config_change::ConfigChange,
+    error::Error, price_round::PriceRound, uptime_stats::UptimeStats,
+};
 const ADMIN_KEY: &str = "admin";
+const PENDING_ADMIN: &str = "pending_admin";
+const ADMIN_HISTORY: &str = "admin_history";
+// Max number of previous admins kept in the history.
+const MAX_ADMIN_HISTORY: u32 = 10;
 const LAST_TIMESTAMP: &str = "last_timestamp";
+const LAST_UPDATE_SOURCE: &str = "last_update_source";
 const RETENTION_PERIOD: &str = "period";
 const ASSETS: &str = "assets";
+const ASSET_COUNT: &str = "asset_count";
 const BASE_ASSET: &str = "base_asset";
 const DECIMALS: &str = "decimals";
 const RESOLUTION: &str = "resolution";
+const CONFIG_VERSION: &str = "config_version";
+const LAYOUT_VERSION: &str = "layout_version";
+const WASM_HASH: &str = "wasm_hash";
+const LAST_UPGRADE_TIMESTAMP: &str = "last_upgrade";
+const UPGRADE_DELAY: &str = "upgrade_delay";
+const GRACE_PERIOD: &str = "grace_period";
+const PRICE_BOUNDS: &str = "price_bounds";
+const STRICT_PRICE_BOUNDS: &str = "strict_bounds";
+const PRICE_CONFIDENCE: &str = "confidence";
+const ASSET_METADATA: &str = "asset_metadata";
+const SUBMITTERS: &str = "submitters";
+const MIN_QUORUM: &str = "min_quorum";
+const PRICE_ROUND: &str = "price_round";
+const SIGNERS: &str = "signers";
+const LAST_UPDATE_HASH: &str = "update_hash";
+const SIGNED_UPDATE: &str = "signed_update";
+const UPTIME_STATS: &str = "uptime_stats";
+const PENDING_CONFIG_CHANGE: &str = "pending_change";
+const CONFIG_TIMELOCK: &str = "config_timelock";
+// Default delay before a proposed config change can be executed (in seconds).
+const DEFAULT_CONFIG_TIMELOCK: u64 = 86_400;
 
 pub trait EnvExtensions {
     fn get_admin(&self) -> Option<Address>;
 
     fn set_admin(&self, admin: &Address);
 
+    fn get_pending_admin(&self) -> Option<Address>;
+
+    fn set_pending_admin(&self, admin: &Address);
+
+    fn clear_pending_admin(&self);
+
+    fn get_admin_history(&self) -> Vec<Address>;
+
+    fn get_pending_config_change(&self) -> Option<(ConfigChange, u64)>;
+
+    fn set_pending_config_change(&self, change: &ConfigChange, unlock_timestamp: u64);
+
+    fn clear_pending_config_change(&self);
+
+    fn get_config_timelock(&self) -> u64;
+
+    fn set_config_timelock(&self, timelock: u64);
+
     fn get_base_asset(&self) -> Asset;
 
     fn set_base_asset(&self, base_asset: &Asset);
 
+    fn get_config_version(&self) -> u32;
+
+    fn set_config_version(&self, version: u32);
+
+    fn get_layout_version(&self) -> u32;
+
+    fn get_wasm_hash(&self) -> Option<BytesN<32>>;
+
+    fn set_wasm_hash(&self, wasm_hash: &BytesN<32>);
+
+    fn get_last_upgrade_timestamp(&self) -> Option<u64>;
+
+    fn set_last_upgrade_timestamp(&self, timestamp: u64);
+
+    fn get_upgrade_delay(&self) -> u64;
+
+    fn set_upgrade_delay(&self, delay: u64);
+
+    fn set_layout_version(&self, version: u32);
+
     fn get_decimals(&self) -> u32;
 
     fn set_decimals(&self, decimals: u32);
@@ -36,21 +104,105 @@ pub trait EnvExtensions {
 
     fn set_retention_period(&self, period: u64);
 
-    fn get_price(&self, asset: u8, timestamp: u64) -> Option<i128>;
+    fn get_grace_period(&self) -> u64;
+
+    fn set_grace_period(&self, grace_period: u64);
+
+    fn get_price(&self, asset: AssetId, timestamp: u64) -> Option<i128>;
+
+    fn asset_price_exists(&self, asset: AssetId, timestamp: u64) -> bool;
+
+    fn get_all_prices_at(&self, timestamp: u64) -> Vec<Option<i128>>;
+
+    fn set_price(&self, asset: AssetId, price: i128, timestamp: u64, ledgers: u32);
+
+    fn get_price_confidence(&self, asset: AssetId, timestamp: u64) -> i128;
+
+    fn set_price_confidence(&self, asset: AssetId, confidence: i128, timestamp: u64, ledgers: u32);
+
+    fn remove_price_confidence(&self, asset: AssetId, timestamp: u64);
+
+    fn remove_price(&self, asset: AssetId, timestamp: u64) -> bool;
 
-    fn set_price(&self, asset: u8, price: i128, timestamp: u64, ledgers: u32);
+    fn get_update_hash(&self, timestamp: u64) -> Option<BytesN<32>>;
+
+    fn set_update_hash(&self, timestamp: u64, hash: &BytesN<32>, ledgers: u32);
+
+    fn is_signed_update_consumed(&self, message: &BytesN<32>) -> bool;
+
+    fn set_signed_update_consumed(&self, message: &BytesN<32>, ledgers: u32);
+
+    fn get_uptime_stats(&self) -> UptimeStats;
+
+    fn set_uptime_stats(&self, stats: &UptimeStats);
+
+    fn count_prices<F: Fn(u64) -> bool>(&self, has_price_fn: F) -> u32;
+
+    fn count_stored_prices(&self, asset: AssetId) -> u32;
+
+    fn extend_price_ttl(&self, asset: AssetId, timestamp: u64, ledgers: u32) -> bool;
+
+    fn bump_all_prices(
+        &self,
+        asset: AssetId,
+        from_timestamp: u64,
+        records: u32,
+        new_ledgers: u32,
+    ) -> u32;
+
+    fn get_price_bounds(&self, asset: AssetId) -> Option<(i128, i128)>;
+
+    fn get_all_price_bounds(&self) -> Map<u32, (i128, i128)>;
+
+    fn set_price_bounds(&self, asset: AssetId, min_price: i128, max_price: i128);
+
+    fn clear_price_bounds(&self, asset: AssetId);
+
+    fn get_asset_metadata(&self, asset: AssetId) -> Option<AssetMetadata>;
+
+    fn set_asset_metadata(&self, asset: AssetId, metadata: &AssetMetadata);
+
+    fn get_strict_price_bounds(&self) -> bool;
+
+    fn set_strict_price_bounds(&self, strict: bool);
+
+    fn get_submitters(&self) -> Vec<Address>;
+
+    fn set_submitters(&self, submitters: &Vec<Address>);
+
+    fn get_signers(&self) -> Vec<BytesN<32>>;
+
+    fn set_signers(&self, signers: &Vec<BytesN<32>>);
+
+    fn get_min_quorum(&self) -> u32;
+
+    fn set_min_quorum(&self, quorum: u32);
+
+    fn get_price_round(&self, round_id: u64) -> Option<PriceRound>;
+
+    fn set_price_round(&self, round_id: u64, round: &PriceRound, ledgers: u32);
+
+    fn remove_price_round(&self, round_id: u64);
 
     fn get_last_timestamp(&self) -> u64;
 
     fn set_last_timestamp(&self, timestamp: u64);
 
+    fn get_last_update_source(&self) -> Option<Address>;
+
+    fn set_last_update_source(&self, source: Option<&Address>);
+
     fn get_assets(&self) -> Vec<Asset>;
 
     fn set_assets(&self, assets: Vec<Asset>);
 
+    fn get_asset_count(&self) -> u32;
+
+    fn set_asset_count(&self, count: u32);
+
     fn set_asset_index(&self, asset: &Asset, index: u32);
 
-    fn get_asset_index(&self, asset: &Asset) -> Option<u8>;
+    fn get_asset_index(&self, asset: &Asset) -> Option<AssetId>;
 
     fn panic_if_not_admin(&self);
 
@@ -67,9 +219,58 @@ impl EnvExtensions for Env {
     }
 
     fn set_admin(&self, admin: &Address) {
+        if let Some(previous_admin) = self.get_admin() {
+            let mut history = self.get_admin_history();
+            history.push_back(previous_admin);
+            //drop the oldest entries on overflow
+            while history.len() > MAX_ADMIN_HISTORY {
+                history.pop_front();
+            }
+            get_instance_storage(self).set(&ADMIN_HISTORY, &history);
+        }
         get_instance_storage(&self).set(&ADMIN_KEY, admin);
     }
 
+    fn get_pending_admin(&self) -> Option<Address> {
+        get_instance_storage(self).get(&PENDING_ADMIN)
+    }
+
+    fn set_pending_admin(&self, admin: &Address) {
+        get_instance_storage(self).set(&PENDING_ADMIN, admin);
+    }
+
+    fn clear_pending_admin(&self) {
+        get_instance_storage(self).remove(&PENDING_ADMIN);
+    }
+
+    fn get_admin_history(&self) -> Vec<Address> {
+        get_instance_storage(self)
+            .get(&ADMIN_HISTORY)
+            .unwrap_or_else(|| Vec::new(self))
+    }
+
+    fn get_pending_config_change(&self) -> Option<(ConfigChange, u64)> {
+        get_instance_storage(self).get(&PENDING_CONFIG_CHANGE)
+    }
+
+    fn set_pending_config_change(&self, change: &ConfigChange, unlock_timestamp: u64) {
+        get_instance_storage(self).set(&PENDING_CONFIG_CHANGE, &(change.clone(), unlock_timestamp));
+    }
+
+    fn clear_pending_config_change(&self) {
+        get_instance_storage(self).remove(&PENDING_CONFIG_CHANGE);
+    }
+
+    fn get_config_timelock(&self) -> u64 {
+        get_instance_storage(self)
+            .get(&CONFIG_TIMELOCK)
+            .unwrap_or(DEFAULT_CONFIG_TIMELOCK)
+    }
+
+    fn set_config_timelock(&self, timelock: u64) {
+        get_instance_storage(self).set(&CONFIG_TIMELOCK, &timelock);
+    }
+
     fn set_base_asset(&self, base_asset: &Asset) {
         get_instance_storage(&self).set(&BASE_ASSET, base_asset)
     }
@@ -78,6 +279,51 @@ impl EnvExtensions for Env {
         get_instance_storage(self).get(&BASE_ASSET).unwrap()
     }
 
+    fn get_config_version(&self) -> u32 {
+        get_instance_storage(self)
+            .get(&CONFIG_VERSION)
+            .unwrap_or_default()
+    }
+
+    fn set_config_version(&self, version: u32) {
+        get_instance_storage(self).set(&CONFIG_VERSION, &version);
+    }
+
+    fn get_layout_version(&self) -> u32 {
+        //contracts deployed before the layout versioning use the initial layout
+        get_instance_storage(self).get(&LAYOUT_VERSION).unwrap_or(1)
+    }
+
+    fn set_layout_version(&self, version: u32) {
+        get_instance_storage(self).set(&LAYOUT_VERSION, &version);
+    }
+
+    fn get_wasm_hash(&self) -> Option<BytesN<32>> {
+        get_instance_storage(self).get(&WASM_HASH)
+    }
+
+    fn set_wasm_hash(&self, wasm_hash: &BytesN<32>) {
+        get_instance_storage(self).set(&WASM_HASH, wasm_hash);
+    }
+
+    fn get_last_upgrade_timestamp(&self) -> Option<u64> {
+        get_instance_storage(self).get(&LAST_UPGRADE_TIMESTAMP)
+    }
+
+    fn set_last_upgrade_timestamp(&self, timestamp: u64) {
+        get_instance_storage(self).set(&LAST_UPGRADE_TIMESTAMP, &timestamp);
+    }
+
+    fn get_upgrade_delay(&self) -> u64 {
+        get_instance_storage(self)
+            .get(&UPGRADE_DELAY)
+            .unwrap_or_default()
+    }
+
+    fn set_upgrade_delay(&self, delay: u64) {
+        get_instance_storage(self).set(&UPGRADE_DELAY, &delay);
+    }
+
     fn get_decimals(&self) -> u32 {
         get_instance_storage(self).get(&DECIMALS).unwrap()
     }
@@ -104,16 +350,44 @@ impl EnvExtensions for Env {
         get_instance_storage(&self).set(&RETENTION_PERIOD, &rdm_period);
     }
 
-    fn get_price(&self, asset: u8, timestamp: u64) -> Option<i128> {
+    fn get_grace_period(&self) -> u64 {
+        get_instance_storage(self)
+            .get(&GRACE_PERIOD)
+            .unwrap_or_else(|| self.get_resolution() as u64 * 2)
+    }
+
+    fn set_grace_period(&self, grace_period: u64) {
+        get_instance_storage(self).set(&GRACE_PERIOD, &grace_period);
+    }
+
+    fn get_price(&self, asset: AssetId, timestamp: u64) -> Option<i128> {
         //build the key for the price
-        let data_key = U128Helper::encode_price_record_key(timestamp, asset);
+        let data_key = U128Helper::encode_price_record_key(timestamp, asset.0);
+        debug_assert!(U128Helper::is_valid_price_key(data_key.to_u128()));
         //get the price
         get_temporary_storage(self).get(&data_key)
     }
 
-    fn set_price(&self, asset: u8, price: i128, timestamp: u64, ledgers_to_live: u32) {
+    fn asset_price_exists(&self, asset: AssetId, timestamp: u64) -> bool {
+        //check the key without loading the price value
+        let data_key = U128Helper::encode_price_record_key(timestamp, asset.0);
+        get_temporary_storage(self).has(&data_key)
+    }
+
+    fn get_all_prices_at(&self, timestamp: u64) -> Vec<Option<i128>> {
+        let temps_storage = get_temporary_storage(self);
+        let mut prices = Vec::new(self);
+        for asset in 0..self.get_asset_count() {
+            //build the key for the price
+            let data_key = U128Helper::encode_price_record_key(timestamp, asset as u8);
+            prices.push_back(temps_storage.get(&data_key));
+        }
+        prices
+    }
+
+    fn set_price(&self, asset: AssetId, price: i128, timestamp: u64, ledgers_to_live: u32) {
         //build the key for the price
-        let data_key = U128Helper::encode_price_record_key(timestamp, asset);
+        let data_key = U128Helper::encode_price_record_key(timestamp, asset.0);
 
         //set the price
         let temps_storage = get_temporary_storage(&self);
@@ -124,6 +398,252 @@ impl EnvExtensions for Env {
         }
     }
 
+    fn get_price_confidence(&self, asset: AssetId, timestamp: u64) -> i128 {
+        let data_key = U128Helper::encode_price_record_key(timestamp, asset.0);
+        //missing confidence is treated as 0
+        get_temporary_storage(self)
+            .get(&(PRICE_CONFIDENCE, data_key))
+            .unwrap_or_default()
+    }
+
+    fn set_price_confidence(
+        &self,
+        asset: AssetId,
+        confidence: i128,
+        timestamp: u64,
+        ledgers_to_live: u32,
+    ) {
+        let data_key = (
+            PRICE_CONFIDENCE,
+            U128Helper::encode_price_record_key(timestamp, asset.0),
+        );
+
+        let temps_storage = get_temporary_storage(self);
+        temps_storage.set(&data_key, &confidence);
+        if ledgers_to_live > 16 {
+            //16 is the minimum number
+            temps_storage.extend_ttl(&data_key, ledgers_to_live, ledgers_to_live)
+        }
+    }
+
+    fn remove_price_confidence(&self, asset: AssetId, timestamp: u64) {
+        let data_key = (
+            PRICE_CONFIDENCE,
+            U128Helper::encode_price_record_key(timestamp, asset.0),
+        );
+        get_temporary_storage(self).remove(&data_key);
+    }
+
+    fn get_update_hash(&self, timestamp: u64) -> Option<BytesN<32>> {
+        get_temporary_storage(self).get(&(LAST_UPDATE_HASH, timestamp))
+    }
+
+    fn set_update_hash(&self, timestamp: u64, hash: &BytesN<32>, ledgers_to_live: u32) {
+        let data_key = (LAST_UPDATE_HASH, timestamp);
+
+        let temps_storage = get_temporary_storage(self);
+        temps_storage.set(&data_key, hash);
+        if ledgers_to_live > 16 {
+            //16 is the minimum number
+            temps_storage.extend_ttl(&data_key, ledgers_to_live, ledgers_to_live)
+        }
+    }
+
+    fn get_uptime_stats(&self) -> UptimeStats {
+        get_instance_storage(self)
+            .get(&UPTIME_STATS)
+            .unwrap_or_default()
+    }
+
+    fn set_uptime_stats(&self, stats: &UptimeStats) {
+        get_instance_storage(self).set(&UPTIME_STATS, stats);
+    }
+
+    fn is_signed_update_consumed(&self, message: &BytesN<32>) -> bool {
+        get_temporary_storage(self).has(&(SIGNED_UPDATE, message.clone()))
+    }
+
+    fn set_signed_update_consumed(&self, message: &BytesN<32>, ledgers_to_live: u32) {
+        let data_key = (SIGNED_UPDATE, message.clone());
+
+        let temps_storage = get_temporary_storage(self);
+        temps_storage.set(&data_key, &true);
+        if ledgers_to_live > 16 {
+            //16 is the minimum number
+            temps_storage.extend_ttl(&data_key, ledgers_to_live, ledgers_to_live)
+        }
+    }
+
+    fn remove_price(&self, asset: AssetId, timestamp: u64) -> bool {
+        //build the key for the price
+        let data_key = U128Helper::encode_price_record_key(timestamp, asset.0);
+
+        let temps_storage = get_temporary_storage(self);
+        if !temps_storage.has(&data_key) {
+            return false;
+        }
+        temps_storage.remove(&data_key);
+        temps_storage.remove(&(PRICE_CONFIDENCE, data_key));
+        true
+    }
+
+    fn count_prices<F: Fn(u64) -> bool>(&self, has_price_fn: F) -> u32 {
+        let mut timestamp = self.get_last_timestamp();
+        let resolution = self.get_resolution() as u64;
+        let retention_period = self.get_retention_period();
+        //records older than the retention period are expired
+        let now = self.ledger().timestamp() * 1000; //convert to milliseconds
+        let retention_start = now.saturating_sub(retention_period);
+        let mut records = (retention_period / resolution).min(crate::MAX_RECORDS as u64);
+        let mut count = 0;
+        while records > 0 && timestamp > 0 && timestamp > retention_start {
+            if has_price_fn(timestamp) {
+                count += 1;
+            }
+            records -= 1;
+            if timestamp < resolution {
+                break;
+            }
+            timestamp -= resolution;
+        }
+        count
+    }
+
+    fn count_stored_prices(&self, asset: AssetId) -> u32 {
+        self.count_prices(|timestamp| self.asset_price_exists(asset, timestamp))
+    }
+
+    fn extend_price_ttl(&self, asset: AssetId, timestamp: u64, ledgers: u32) -> bool {
+        //build the key for the price
+        let data_key = U128Helper::encode_price_record_key(timestamp, asset.0);
+
+        let temps_storage = get_temporary_storage(self);
+        if !temps_storage.has(&data_key) {
+            return false;
+        }
+        temps_storage.extend_ttl(&data_key, ledgers, ledgers);
+        let confidence_key = (PRICE_CONFIDENCE, data_key);
+        if temps_storage.has(&confidence_key) {
+            temps_storage.extend_ttl(&confidence_key, ledgers, ledgers);
+        }
+        true
+    }
+
+    fn bump_all_prices(
+        &self,
+        asset: AssetId,
+        from_timestamp: u64,
+        mut records: u32,
+        new_ledgers: u32,
+    ) -> u32 {
+        let mut timestamp = from_timestamp;
+        let resolution = self.get_resolution() as u64;
+        let mut bumped = 0;
+        while records > 0 && timestamp > 0 {
+            if self.extend_price_ttl(asset, timestamp, new_ledgers) {
+                bumped += 1;
+            }
+            records -= 1;
+            if timestamp < resolution {
+                break;
+            }
+            timestamp -= resolution;
+        }
+        bumped
+    }
+
+    fn get_price_bounds(&self, asset: AssetId) -> Option<(i128, i128)> {
+        self.get_all_price_bounds().get(asset.0 as u32)
+    }
+
+    fn get_all_price_bounds(&self) -> Map<u32, (i128, i128)> {
+        get_instance_storage(self)
+            .get(&PRICE_BOUNDS)
+            .unwrap_or_else(|| Map::new(self))
+    }
+
+    fn set_price_bounds(&self, asset: AssetId, min_price: i128, max_price: i128) {
+        let mut bounds = self.get_all_price_bounds();
+        bounds.set(asset.0 as u32, (min_price, max_price));
+        get_instance_storage(self).set(&PRICE_BOUNDS, &bounds);
+    }
+
+    fn clear_price_bounds(&self, asset: AssetId) {
+        let mut bounds = self.get_all_price_bounds();
+        bounds.remove(asset.0 as u32);
+        get_instance_storage(self).set(&PRICE_BOUNDS, &bounds);
+    }
+
+    fn get_asset_metadata(&self, asset: AssetId) -> Option<AssetMetadata> {
+        get_persistent_storage(self).get(&(ASSET_METADATA, asset.0 as u32))
+    }
+
+    fn set_asset_metadata(&self, asset: AssetId, metadata: &AssetMetadata) {
+        let persistent_storage = get_persistent_storage(self);
+        let data_key = (ASSET_METADATA, asset.0 as u32);
+        persistent_storage.set(&data_key, metadata);
+        //keep the metadata alive as long as possible, it is not rewritten on price updates
+        let max_ttl = self.storage().max_ttl();
+        persistent_storage.extend_ttl(&data_key, max_ttl, max_ttl);
+    }
+
+    fn get_strict_price_bounds(&self) -> bool {
+        get_instance_storage(self)
+            .get(&STRICT_PRICE_BOUNDS)
+            .unwrap_or_default()
+    }
+
+    fn set_strict_price_bounds(&self, strict: bool) {
+        get_instance_storage(self).set(&STRICT_PRICE_BOUNDS, &strict);
+    }
+
+    fn get_submitters(&self) -> Vec<Address> {
+        get_instance_storage(self)
+            .get(&SUBMITTERS)
+            .unwrap_or_else(|| Vec::new(self))
+    }
+
+    fn set_submitters(&self, submitters: &Vec<Address>) {
+        get_instance_storage(self).set(&SUBMITTERS, submitters);
+    }
+
+    fn get_signers(&self) -> Vec<BytesN<32>> {
+        get_instance_storage(self)
+            .get(&SIGNERS)
+            .unwrap_or_else(|| Vec::new(self))
+    }
+
+    fn set_signers(&self, signers: &Vec<BytesN<32>>) {
+        get_instance_storage(self).set(&SIGNERS, signers);
+    }
+
+    fn get_min_quorum(&self) -> u32 {
+        get_instance_storage(self).get(&MIN_QUORUM).unwrap_or(1)
+    }
+
+    fn set_min_quorum(&self, quorum: u32) {
+        get_instance_storage(self).set(&MIN_QUORUM, &quorum);
+    }
+
+    fn get_price_round(&self, round_id: u64) -> Option<PriceRound> {
+        get_temporary_storage(self).get(&(PRICE_ROUND, round_id))
+    }
+
+    fn set_price_round(&self, round_id: u64, round: &PriceRound, ledgers_to_live: u32) {
+        let data_key = (PRICE_ROUND, round_id);
+
+        let temps_storage = get_temporary_storage(self);
+        temps_storage.set(&data_key, round);
+        if ledgers_to_live > 16 {
+            //16 is the minimum number
+            temps_storage.extend_ttl(&data_key, ledgers_to_live, ledgers_to_live)
+        }
+    }
+
+    fn remove_price_round(&self, round_id: u64) {
+        get_temporary_storage(self).remove(&(PRICE_ROUND, round_id));
+    }
+
     fn get_last_timestamp(&self) -> u64 {
         //get the marker
         get_instance_storage(&self)
@@ -135,6 +655,17 @@ impl EnvExtensions for Env {
         get_instance_storage(&self).set(&LAST_TIMESTAMP, &timestamp);
     }
 
+    fn get_last_update_source(&self) -> Option<Address> {
+        get_instance_storage(self).get(&LAST_UPDATE_SOURCE)
+    }
+
+    fn set_last_update_source(&self, source: Option<&Address>) {
+        match source {
+            Some(source) => get_instance_storage(self).set(&LAST_UPDATE_SOURCE, source),
+            None => get_instance_storage(self).remove(&LAST_UPDATE_SOURCE),
+        }
+    }
+
     fn get_assets(&self) -> Vec<Asset> {
         get_instance_storage(&self)
             .get(&ASSETS)
@@ -145,7 +676,19 @@ impl EnvExtensions for Env {
         get_instance_storage(&self).set(&ASSETS, &assets);
     }
 
+    fn get_asset_count(&self) -> u32 {
+        //the counter is missing until `migrate_v1_to_v2` is invoked for the contracts deployed before it
+        get_instance_storage(self)
+            .get(&ASSET_COUNT)
+            .unwrap_or_default()
+    }
+
+    fn set_asset_count(&self, count: u32) {
+        get_instance_storage(self).set(&ASSET_COUNT, &count);
+    }
+
     fn set_asset_index(&self, asset: &Asset, index: u32) {
+        let count = self.get_asset_count();
         match asset {
             Asset::Stellar(address) => {
                 get_instance_storage(&self).set(&address, &index);
@@ -154,9 +697,10 @@ impl EnvExtensions for Env {
                 get_instance_storage(&self).set(&symbol, &index);
             }
         }
+        get_instance_storage(self).set(&ASSET_COUNT, &(count + 1));
     }
 
-    fn get_asset_index(&self, asset: &Asset) -> Option<u8> {
+    fn get_asset_index(&self, asset: &Asset) -> Option<AssetId> {
         let index: Option<u32>;
         match asset {
             Asset::Stellar(address) => {
@@ -169,7 +713,7 @@ impl EnvExtensions for Env {
         if index.is_none() {
             return None;
         }
-        return Some(index.unwrap() as u8);
+        return Some(AssetId(index.unwrap() as u8));
     }
 
     fn panic_if_not_admin(&self) {
@@ -185,6 +729,10 @@ fn get_instance_storage(e: &Env) -> Instance {
     e.storage().instance()
 }
 
+fn get_persistent_storage(e: &Env) -> Persistent {
+    e.storage().persistent()
+}
+
 fn get_temporary_storage(e: &Env) -> Temporary {
     e.storage().temporary()
 }
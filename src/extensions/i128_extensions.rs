@@ -15,12 +15,33 @@ pub trait I128Extensions {
     // Returns:
     // - Division result with specified rounding behavior.
     fn fixed_div_floor(self, y: i128, decimals: u32) -> i128;
+
+    // Multiplies two i128 numbers, considering decimal places.
+    //
+    // Arguments:
+    // - self: The multiplicand.
+    // - y: The multiplier.
+    // - decimals: Number of decimal places of the multiplier.
+    //
+    // Behavior:
+    // - Rounds down towards zero.
+    //
+    // Panic:
+    // - If the multiplication overflows.
+    //
+    // Returns:
+    // - Multiplication result with specified rounding behavior.
+    fn fixed_mul_floor(self, y: i128, decimals: u32) -> i128;
 }
 
 impl I128Extensions for i128 {
     fn fixed_div_floor(self, y: i128, decimals: u32) -> i128 {
         div_floor(self, y, decimals)
     }
+
+    fn fixed_mul_floor(self, y: i128, decimals: u32) -> i128 {
+        mul_floor(self, y, decimals)
+    }
 }
 
 fn div_floor(dividend: i128, divisor: i128, decimals: u32) -> i128 {
@@ -40,3 +61,34 @@ fn div_floor(dividend: i128, divisor: i128, decimals: u32) -> i128 {
     }
     vdividend / vdivisor
 }
+
+fn mul_floor(x: i128, y: i128, decimals: u32) -> i128 {
+    x.checked_mul(y).expect("multiplication overflow") / 10_i128.pow(decimals)
+}
+
+// Calculates the weighted average of the values.
+//
+// Arguments:
+// - values: Pairs of values and their weights. The iterator is traversed twice.
+//
+// Behavior:
+// - Rounds towards zero.
+// - Avoids overflow of intermediate products by splitting each value into the quotient and remainder of the total weight.
+//
+// Returns:
+// - Weighted average or None if there are no values or all weights are zero.
+pub fn weighted_average<I: Iterator<Item = (i128, u32)> + Clone>(values: I) -> Option<i128> {
+    let total_weight: i128 = values.clone().map(|(_, weight)| weight as i128).sum();
+    if total_weight == 0 {
+        return None;
+    }
+    //value * weight / total_weight == (value / total_weight) * weight + (value % total_weight) * weight / total_weight
+    let mut quotient_sum: i128 = 0;
+    let mut remainder_sum: i128 = 0;
+    for (value, weight) in values {
+        let weight = weight as i128;
+        quotient_sum += (value / total_weight) * weight;
+        remainder_sum += (value % total_weight) * weight;
+    }
+    Some(quotient_sum + remainder_sum / total_weight)
+}
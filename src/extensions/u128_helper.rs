@@ -1,7 +1,47 @@
+use soroban_sdk::{Env, TryFromVal, Val};
+
 pub struct U128Helper;
 
+// The storage key of a price record. Can only be built with `U128Helper::encode_price_record_key`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PriceKey(u128);
+
+impl PriceKey {
+    pub fn to_u128(self) -> u128 {
+        self.0
+    }
+}
+
+impl TryFromVal<Env, PriceKey> for Val {
+    type Error = soroban_sdk::Error;
+
+    fn try_from_val(env: &Env, key: &PriceKey) -> Result<Self, Self::Error> {
+        Val::try_from_val(env, &key.0)
+    }
+}
+
+impl TryFromVal<Env, Val> for PriceKey {
+    type Error = soroban_sdk::Error;
+
+    fn try_from_val(env: &Env, val: &Val) -> Result<Self, Self::Error> {
+        u128::try_from_val(env, val).map(PriceKey)
+    }
+}
+
 impl U128Helper {
-    pub fn encode_price_record_key(val_u64: u64, val_u8: u8) -> u128 {
-        (val_u64 as u128) << 64 | val_u8 as u128
+    // Encodes the price record key. The timestamp occupies bits 64-127 (always fits as it is u64),
+    // the asset index occupies bits 0-7, bits 8-63 are unused and always zero.
+    pub fn encode_price_record_key(val_u64: u64, val_u8: u8) -> PriceKey {
+        PriceKey((val_u64 as u128) << 64 | val_u8 as u128)
+    }
+
+    #[cfg(test)]
+    pub fn decode_price_record_key(key: PriceKey) -> (u64, u8) {
+        ((key.0 >> 64) as u64, (key.0 & 0xFF) as u8)
+    }
+
+    // Checks that the unused bits of the raw price record key are zero.
+    pub fn is_valid_price_key(key: u128) -> bool {
+        (key >> 8) & 0x00FF_FFFF_FFFF_FFFF == 0
     }
 }
@@ -1,17 +1,29 @@
 pub trait U64Extensions {
     fn get_normalized_timestamp(self, timeframe: u64) -> u64;
+    fn clamp_to_resolution(self, resolution_ms: u64) -> u64;
     fn is_valid_timestamp(self, timeframe: u64) -> bool;
+    fn is_within_window(&self, reference_ts: u64, window_ms: u64) -> bool;
 }
 
 impl U64Extensions for u64 {
     fn get_normalized_timestamp(self, timeframe: u64) -> u64 {
-        if (self == 0) || (timeframe == 0) {
+        self.clamp_to_resolution(timeframe)
+    }
+
+    fn clamp_to_resolution(self, resolution_ms: u64) -> u64 {
+        if (self == 0) || (resolution_ms == 0) {
             return 0;
         }
-        (self / timeframe) * timeframe
+        //floor to the resolution boundary
+        (self / resolution_ms) * resolution_ms
     }
 
     fn is_valid_timestamp(self, timeframe: u64) -> bool {
         self == Self::get_normalized_timestamp(self, timeframe)
     }
+
+    fn is_within_window(&self, reference_ts: u64, window_ms: u64) -> bool {
+        //timestamps after the reference are never within the window
+        *self <= reference_ts && reference_ts - *self < window_ms
+    }
 }
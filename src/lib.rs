@@ -1,15 +1,57 @@
 #![no_std]
 
+#[cfg(not(target_family = "wasm"))]
+extern crate alloc;
+
+mod events;
 mod extensions;
+mod oracle_interface;
 mod test;
 mod types;
 
-use extensions::i128_extensions::I128Extensions;
+use extensions::i128_extensions::{weighted_average, I128Extensions};
 use extensions::{env_extensions::EnvExtensions, u64_extensions::U64Extensions};
-use soroban_sdk::{contract, contractimpl, panic_with_error, Address, BytesN, Env, Vec};
+use oracle_interface::OracleInterface;
+use soroban_sdk::{
+    contract, contractimpl, log, panic_with_error, xdr::ToXdr, Address, Bytes, BytesN, Env, Map,
+    Vec,
+};
 use types::asset::Asset;
+use types::asset_id::AssetId;
 use types::error::Error;
-use types::{config_data::ConfigData, price_data::PriceData};
+use types::{
+    aggregated_price_data::AggregatedPriceData, asset_metadata::AssetMetadata,
+    change_type::ChangeType, config_change::ConfigChange, config_data::ConfigData,
+    ohlc_data::OHLCData, ohlcv_data::OHLCVData, oracle_info::OracleInfo, price_data::PriceData,
+    price_round::PriceRound, price_update_item::PriceUpdateItem,
+    price_with_confidence::PriceWithConfidence, prune_result::PruneResult,
+    pyth_price_update::PythPriceUpdate, uptime_stats::UptimeStats,
+};
+
+// Max number of quoted assets. Asset indexes are stored as u8, and a single price update can hold prices for all of them.
+const MAX_ASSETS: u32 = 255;
+
+// Index used by `validate_price_update` for the issues related to the whole snapshot. Asset indexes never reach it.
+const SNAPSHOT_ISSUE_INDEX: u32 = u8::MAX as u32;
+const BASIS_POINTS: u32 = 10_000;
+// Storage layout version expected by the contract code. Bumped on every storage key schema change.
+const STORAGE_LAYOUT_VERSION: u32 = 2;
+// Domain tag prepended to the messages signed for `set_price_signed`.
+const SIGNED_UPDATE_DOMAIN: &[u8] = b"reflector:set_price_signed";
+// Max number of records returned by the history queries.
+const MAX_RECORDS: u32 = 20;
+// Max number of assets returned by `storage_stats`.
+const MAX_STATS_ASSETS: u32 = 10;
+// Max number of timestamps returned by `last_n_timestamps`.
+const MAX_TIMESTAMPS: u32 = 50;
+const MAX_PRUNE_RECORDS: u32 = 20;
+// Max number of resolution steps extended by a single `refresh_retention` call.
+const MAX_REFRESH_RECORDS: u32 = 20;
+// Max number of assets processed by a single maintenance call.
+const MAX_MAINTENANCE_ASSETS: u32 = 5;
+const MAX_PAIRS: u32 = 200;
+const MIN_DECIMALS: u32 = 1;
+const MAX_DECIMALS: u32 = 18;
 
 #[contract]
 pub struct PriceOracleContract;
@@ -17,6 +59,7 @@ pub struct PriceOracleContract;
 #[contractimpl]
 impl PriceOracleContract {
     // Returns the base asset the price is reported in.
+    // SEP-40: required oracle method `base`.
     //
     // # Returns
     //
@@ -26,6 +69,7 @@ impl PriceOracleContract {
     }
 
     // Returns the number of decimal places used to represent price for all assets quoted by the oracle.
+    // SEP-40: required oracle method `decimals`.
     //
     // # Returns
     //
@@ -35,6 +79,7 @@ impl PriceOracleContract {
     }
 
     // Returns the default tick period timeframe (in seconds).
+    // SEP-40: required oracle method `resolution`.
     //
     // # Returns
     //
@@ -43,6 +88,15 @@ impl PriceOracleContract {
         e.get_resolution() / 1000
     }
 
+    // Returns the raw default tick period timeframe (in milliseconds). `resolution()` equals `resolution_ms() / 1000`.
+    //
+    // # Returns
+    //
+    // Price feed resolution (in milliseconds)
+    pub fn resolution_ms(e: Env) -> u32 {
+        e.get_resolution()
+    }
+
     // Returns the historical records retention period (in seconds).
     //
     // # Returns
@@ -57,7 +111,59 @@ impl PriceOracleContract {
         }
     }
 
+    // Returns the raw historical records retention period (in milliseconds). `period()` equals `period_ms() / 1000`.
+    //
+    // # Returns
+    //
+    // History retention period (in milliseconds)
+    pub fn period_ms(e: Env) -> Option<u64> {
+        let period = e.get_retention_period();
+        if period == 0 {
+            return None;
+        }
+        Some(period)
+    }
+
+    // Returns the price update regularity stats.
+    //
+    // # Returns
+    //
+    // Total number of recorded snapshots, last snapshot timestamp (in seconds), and the number of resolution intervals
+    // missed since the last regular update
+    pub fn oracle_uptime_stats(e: Env) -> UptimeStats {
+        e.get_uptime_stats()
+    }
+
+    // Returns the max allowed age of the last price record to be considered actual (in seconds).
+    //
+    // # Returns
+    //
+    // Last price grace period (in seconds)
+    pub fn grace_period(e: Env) -> u64 {
+        e.get_grace_period() / 1000 //convert to seconds
+    }
+
+    // Returns the accounts that provide price data to the contract: the admin and the authorized round submitters.
+    // SEP-40: optional oracle method `sources`.
+    //
+    // # Returns
+    //
+    // Price data sources
+    pub fn sources(e: Env) -> Vec<Address> {
+        let mut sources = Vec::new(&e);
+        if let Some(admin) = e.get_admin() {
+            sources.push_back(admin);
+        }
+        for submitter in e.get_submitters().iter() {
+            if !sources.contains(&submitter) {
+                sources.push_back(submitter);
+            }
+        }
+        sources
+    }
+
     // Returns all assets quoted by the contract.
+    // SEP-40: required oracle method `assets`.
     //
     // # Returns
     //
@@ -66,6 +172,103 @@ impl PriceOracleContract {
         e.get_assets()
     }
 
+    // Checks whether the asset is quoted by the contract.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to check
+    //
+    // # Returns
+    //
+    // True if the asset is supported
+    pub fn is_asset_supported(e: Env, asset: Asset) -> bool {
+        e.get_asset_index(&asset).is_some()
+    }
+
+    // Checks whether the cross price for the pair of assets can be quoted by the contract.
+    //
+    // # Arguments
+    //
+    // * `base` - Base asset
+    // * `quote` - Quote asset
+    //
+    // # Returns
+    //
+    // True if both assets are supported
+    pub fn is_pair_supported(e: Env, base: Asset, quote: Asset) -> bool {
+        get_asset_pair_indexes(&e, base, quote).is_some()
+    }
+
+    // Returns ordered pairs of assets the cross price can be quoted for.
+    //
+    // # Arguments
+    //
+    // * `start_index` - Index of the first pair to return
+    // * `max_pairs` - Max number of pairs to return (capped at 200)
+    //
+    // # Returns
+    //
+    // Supported (base_asset, quote_asset) pairs
+    pub fn get_supported_pairs(e: Env, start_index: u32, max_pairs: u32) -> Vec<(Asset, Asset)> {
+        let assets = e.get_assets();
+        let mut pairs = Vec::new(&e);
+        let assets_count = assets.len();
+        if assets_count < 2 {
+            return pairs;
+        }
+        //each asset is paired with all other assets
+        let total_pairs = assets_count * (assets_count - 1);
+        let end_index = total_pairs.min(start_index.saturating_add(max_pairs.min(MAX_PAIRS)));
+        for pair_index in start_index..end_index {
+            let base_index = pair_index / (assets_count - 1);
+            let mut quote_index = pair_index % (assets_count - 1);
+            //skip self-pair
+            if quote_index >= base_index {
+                quote_index += 1;
+            }
+            pairs.push_back((
+                assets.get_unchecked(base_index),
+                assets.get_unchecked(quote_index),
+            ));
+        }
+        pairs
+    }
+
+    // Returns the number of stored price records for the page of assets quoted by the contract.
+    //
+    // # Arguments
+    //
+    // * `offset` - Index of the first asset
+    // * `limit` - Number of assets to return (max 10)
+    //
+    // # Returns
+    //
+    // Number of price records available within the retention period (up to 20 most recent records) for each asset of the page
+    pub fn storage_stats(e: Env, offset: u32, limit: u32) -> Vec<(Asset, u32)> {
+        let mut stats = Vec::new(&e);
+        let assets = e.get_assets();
+        let end = offset
+            .saturating_add(limit.min(MAX_STATS_ASSETS))
+            .min(assets.len());
+        for asset_index in offset..end {
+            stats.push_back((
+                assets.get_unchecked(asset_index),
+                e.count_stored_prices(AssetId(asset_index as u8)),
+            ));
+        }
+        stats
+    }
+
+    // Returns the version of the contract storage layout. Contract upgrades that change the storage key schema bump the version
+    // and require a data migration before the prices can be recorded.
+    //
+    // # Returns
+    //
+    // Storage layout version
+    pub fn storage_layout_version(e: Env) -> u32 {
+        e.get_layout_version()
+    }
+
     // Returns the most recent price update timestamp in seconds.
     //
     // # Returns
@@ -75,7 +278,68 @@ impl PriceOracleContract {
         e.get_last_timestamp() / 1000 //convert to seconds
     }
 
+    // Checks whether the most recent price update is newer than the given timestamp and contains the asset price.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to check
+    // * `since_timestamp` - Timestamp to compare with (in seconds)
+    //
+    // # Returns
+    //
+    // True if the last update happened after the timestamp and has a price for the asset
+    pub fn has_prices_after(e: Env, asset: Asset, since_timestamp: u64) -> bool {
+        let asset_index = match e.get_asset_index(&asset) {
+            Some(asset_index) => asset_index,
+            None => return false,
+        };
+        let last_timestamp = e.get_last_timestamp();
+        last_timestamp > since_timestamp * 1000 //convert to milliseconds
+            && e.asset_price_exists(asset_index, last_timestamp)
+    }
+
+    // Returns the most recent price update timestamps in seconds.
+    //
+    // # Arguments
+    //
+    // * `n` - Number of resolution steps to inspect starting from the last update (max 50)
+    //
+    // # Returns
+    //
+    // Timestamps with a stored price of the first asset within the inspected steps and the retention period, newest first
+    pub fn last_n_timestamps(e: Env, n: u32) -> Vec<u64> {
+        let mut timestamps = Vec::new(&e);
+        let mut timestamp = e.get_last_timestamp();
+        let resolution = e.get_resolution() as u64;
+        //records older than the retention period are expired
+        let retention_start = now(&e).saturating_sub(e.get_retention_period());
+        //limit the number of inspected steps, gaps count as steps
+        let mut steps = n.min(MAX_TIMESTAMPS);
+        while steps > 0 && timestamp > retention_start {
+            //the first asset is used as a proxy to keep the number of reads bounded
+            if e.asset_price_exists(AssetId(0), timestamp) {
+                timestamps.push_back(timestamp / 1000); //convert to seconds
+            }
+            steps -= 1;
+            if timestamp < resolution {
+                break;
+            }
+            timestamp -= resolution;
+        }
+        timestamps
+    }
+
+    // Returns the account that submitted the most recent price update.
+    //
+    // # Returns
+    //
+    // Source of the last recorded price update or None if it was submitted with an off-chain signature or there were no updates
+    pub fn last_update_source(e: Env) -> Option<Address> {
+        e.get_last_update_source()
+    }
+
     // Returns price in base asset at specific timestamp.
+    // SEP-40: required oracle method `price`.
     //
     // # Arguments
     //
@@ -93,7 +357,24 @@ impl PriceOracleContract {
         get_price_data(&e, asset, normalized_timestamp)
     }
 
+    // Returns prices of all quoted assets at specific timestamp.
+    //
+    // # Arguments
+    //
+    // * `timestamp` - Timestamp in seconds
+    //
+    // # Returns
+    //
+    // Prices ordered as the assets returned by `assets`, None for the assets without a price record at the timestamp
+    pub fn all_prices_at(e: Env, timestamp: u64) -> Vec<Option<i128>> {
+        let resolution = e.get_resolution();
+        let normalized_timestamp = //convert to milliseconds and normalize
+            (timestamp * 1000).get_normalized_timestamp(resolution.into());
+        e.get_all_prices_at(normalized_timestamp)
+    }
+
     // Returns the most recent price for an asset.
+    // SEP-40: required oracle method `lastprice`.
     //
     // # Arguments
     //
@@ -112,7 +393,91 @@ impl PriceOracleContract {
         get_price_data(&e, asset, timestamp)
     }
 
+    // Returns the most recent price for an asset along with its age relative to the ledger timestamp.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to quote
+    // * `max_age_seconds` - Max allowed price age in seconds
+    //
+    // # Returns
+    //
+    // The most recent price with staleness data for the given asset or None if the asset is not supported
+    pub fn lastprice_with_staleness(
+        e: Env,
+        asset: Asset,
+        max_age_seconds: u64,
+    ) -> Option<AggregatedPriceData> {
+        let price_data = Self::lastprice(e.clone(), asset)?;
+        let age_seconds = e.ledger().timestamp().saturating_sub(price_data.timestamp);
+        Some(AggregatedPriceData {
+            price: price_data.price,
+            timestamp: price_data.timestamp,
+            age_seconds,
+            is_fresh: age_seconds <= max_age_seconds,
+            max_age_seconds,
+        })
+    }
+
+    // Returns the most recent price for an asset along with its confidence interval.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to quote
+    //
+    // # Returns
+    //
+    // The most recent price with confidence for the given asset or None if the asset is not supported
+    pub fn lastprice_with_confidence(e: Env, asset: Asset) -> Option<PriceWithConfidence> {
+        let timestamp = obtain_record_timestamp(&e);
+        if timestamp == 0 {
+            return None;
+        }
+        let asset_index = e.get_asset_index(&asset)?;
+        let price = e.get_price(asset_index, timestamp)?;
+        Some(PriceWithConfidence {
+            price,
+            confidence: e.get_price_confidence(asset_index, timestamp),
+            timestamp: timestamp / 1000, //convert to seconds
+        })
+    }
+
+    // Returns the most recent price for an asset recorded at or before the given timestamp.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to quote
+    // * `max_timestamp` - Timestamp in seconds
+    //
+    // # Returns
+    //
+    // The most recent price record at or before the given timestamp or None if there were no records found within 20 resolution steps
+    // (or the retention period, if shorter)
+    pub fn lastprice_before(e: Env, asset: Asset, max_timestamp: u64) -> Option<PriceData> {
+        let asset_index = e.get_asset_index(&asset)?;
+        let resolution = e.get_resolution() as u64;
+        let mut timestamp = //convert to milliseconds and normalize
+            (max_timestamp * 1000).get_normalized_timestamp(resolution);
+        //there are no records after the last update
+        timestamp = timestamp.min(e.get_last_timestamp());
+        // Limit the number of steps
+        let mut records = (e.get_retention_period() / resolution).min(MAX_RECORDS as u64);
+        while records > 0 && timestamp > 0 {
+            let price = get_price_data_by_index(&e, asset_index, timestamp);
+            if price.is_some() {
+                return price;
+            }
+            records -= 1;
+            if timestamp < resolution {
+                break;
+            }
+            timestamp -= resolution;
+        }
+        None
+    }
+
     // Returns last N price records for the given asset.
+    // SEP-40: required oracle method `prices`.
     //
     // # Arguments
     //
@@ -134,6 +499,81 @@ impl PriceOracleContract {
         )
     }
 
+    // Returns the hash of the last N price records for the given asset. Each record is serialized as
+    // little-endian timestamp in seconds (8 bytes) followed by little-endian price (16 bytes), newest record first.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to quote
+    // * `records` - Number of records to hash
+    //
+    // # Returns
+    //
+    // Sha256 hash of the price records or None if the asset is not supported or there are no prices
+    pub fn price_history_hash(e: Env, asset: Asset, records: u32) -> Option<BytesN<32>> {
+        let prices = Self::prices(e.clone(), asset, records)?;
+        if prices.is_empty() {
+            return None;
+        }
+        let mut data = Bytes::new(&e);
+        for price_data in prices.iter() {
+            data.extend_from_array(&price_data.timestamp.to_le_bytes());
+            data.extend_from_array(&price_data.price.to_le_bytes());
+        }
+        Some(e.crypto().sha256(&data))
+    }
+
+    // Returns last N price records for the given asset, including gaps.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to quote
+    // * `records` - Number of records to return (capped at 20)
+    //
+    // # Returns
+    //
+    // Prices for the given asset, one per resolution step, with None for missing records, or None if the asset is not supported
+    pub fn prices_with_gaps(e: Env, asset: Asset, records: u32) -> Option<Vec<Option<PriceData>>> {
+        let asset_index = e.get_asset_index(&asset)?;
+        let mut timestamp = obtain_record_timestamp(&e);
+        if timestamp == 0 {
+            return None;
+        }
+
+        let mut prices = Vec::new(&e);
+        let resolution = e.get_resolution() as u64;
+
+        // Limit the number of records
+        let mut records = records.min(MAX_RECORDS);
+
+        while records > 0 {
+            prices.push_back(get_price_data_by_index(&e, asset_index, timestamp));
+            records -= 1;
+            if timestamp < resolution {
+                break;
+            }
+            timestamp -= resolution;
+        }
+        Some(prices)
+    }
+
+    // Returns the number of price records available for the given asset within the retention period (up to 20 most recent records).
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to quote
+    //
+    // # Returns
+    //
+    // Number of available price records for the given asset
+    pub fn price_count(e: Env, asset: Asset) -> u32 {
+        let asset_index = e.get_asset_index(&asset);
+        if asset_index.is_none() {
+            return 0;
+        }
+        e.count_stored_prices(asset_index.unwrap())
+    }
+
     // Returns the most recent cross price record for the pair of assets.
     //
     // # Arguments
@@ -176,6 +616,60 @@ impl PriceOracleContract {
         get_x_price(&e, base_asset, quote_asset, normalized_timestamp, decimals)
     }
 
+    // Returns the cross price for the pair of assets at specific timestamp, bridged through the base asset.
+    //
+    // # Arguments
+    //
+    // * `a` - Base asset
+    // * `_bridge` - Bridge asset, the contract base asset is always used as the bridge
+    // * `c` - Quote asset
+    // * `timestamp` - Timestamp in seconds
+    //
+    // # Returns
+    //
+    // Cross price (a_price/c_price) at the given timestamp or None if there were no records found for quoted assets at specific timestamp
+    pub fn three_way_price(
+        e: Env,
+        a: Asset,
+        _bridge: Asset,
+        c: Asset,
+        timestamp: u64,
+    ) -> Option<PriceData> {
+        Self::x_price(e, a, c, timestamp)
+    }
+
+    // Returns the cross price for the pair of assets at specific timestamp, explicitly bridged through the given asset.
+    //
+    // # Arguments
+    //
+    // * `a` - Base asset
+    // * `bridge` - Bridge asset
+    // * `c` - Quote asset
+    // * `timestamp` - Timestamp in seconds
+    //
+    // # Returns
+    //
+    // Cross price (a_price/bridge_price * bridge_price/c_price) at the given timestamp or None if any of the assets is not supported or there were no records found at specific timestamp
+    pub fn three_way_price_explicit(
+        e: Env,
+        a: Asset,
+        bridge: Asset,
+        c: Asset,
+        timestamp: u64,
+    ) -> Option<PriceData> {
+        let normalized_timestamp = //convert to milliseconds and normalize
+            (timestamp * 1000).get_normalized_timestamp(e.get_resolution().into());
+        let decimals = e.get_decimals();
+        let a_bridge_price = get_x_price(&e, a, bridge.clone(), normalized_timestamp, decimals)?;
+        let bridge_c_price = get_x_price(&e, bridge, c, normalized_timestamp, decimals)?;
+        Some(get_normalized_price_data(
+            a_bridge_price
+                .price
+                .fixed_mul_floor(bridge_c_price.price, decimals),
+            normalized_timestamp,
+        ))
+    }
+
     // Returns last N cross price records of for the pair of assets.
     //
     // # Arguments
@@ -206,6 +700,28 @@ impl PriceOracleContract {
         )
     }
 
+    // Returns the number of cross price records available for the pair of assets within the retention period (up to 20 most recent records).
+    //
+    // # Arguments
+    //
+    // * `base_asset` - Base asset
+    // * `quote_asset` - Quote asset
+    //
+    // # Returns
+    //
+    // Number of available cross price records for the given assets
+    pub fn x_price_count(e: Env, base_asset: Asset, quote_asset: Asset) -> u32 {
+        let asset_pair_indexes = get_asset_pair_indexes(&e, base_asset, quote_asset);
+        if asset_pair_indexes.is_none() {
+            return 0;
+        }
+        let (base_asset, quote_asset) = asset_pair_indexes.unwrap();
+        e.count_prices(|timestamp| {
+            e.asset_price_exists(base_asset, timestamp)
+                && e.asset_price_exists(quote_asset, timestamp)
+        })
+    }
+
     // Returns the time-weighted average price for the given asset over N recent records.
     //
     // # Arguments
@@ -254,42 +770,285 @@ impl PriceOracleContract {
         )
     }
 
-    // Returns current protocol version of the contract.
+    // Returns the time-weighted average price for the given asset over the given time range.
     //
-    // # Returns
+    // # Arguments
     //
-    // Contract protocol version
-    pub fn version(_e: Env) -> u32 {
-        env!("CARGO_PKG_VERSION")
-            .split(".")
-            .next()
-            .unwrap()
-            .parse::<u32>()
-            .unwrap()
-    }
-
-    //Admin section
-
-    // Returns admin address of the contract.
+    // * `asset` - Asset to quote
+    // * `from_ts` - Range start timestamp in seconds (inclusive)
+    // * `to_ts` - Range end timestamp in seconds (inclusive)
     //
     // # Returns
     //
-    // Contract admin account address
-    pub fn admin(e: Env) -> Option<Address> {
-        e.get_admin()
+    // TWAP for the given asset over the prices found in the range (up to 20 resolution steps back from the range end) or None if there were no records found
+    pub fn twap_range(e: Env, asset: Asset, from_ts: u64, to_ts: u64) -> Option<i128> {
+        let asset_index = e.get_asset_index(&asset)?;
+        get_twap_range(
+            &e,
+            |timestamp| get_price_data_by_index(&e, asset_index, timestamp),
+            from_ts,
+            to_ts,
+        )
     }
 
-    // Updates the contract configuration parameters. Can be invoked only by the admin account.
+    // Returns the time-weighted average cross price for the given asset pair over the given time range.
     //
     // # Arguments
     //
-    // * `admin` - Admin account address
-    // * `config` - Configuration parameters
-    //
+    // * `base_asset` - Base asset
+    // * `quote_asset` - Quote asset
+    // * `from_ts` - Range start timestamp in seconds (inclusive)
+    // * `to_ts` - Range end timestamp in seconds (inclusive)
+    //
+    // # Returns
+    //
+    // TWAP (base_asset_price/quote_asset_price) over the cross prices found in the range (up to 20 resolution steps back from the range end) or None if there were no records found
+    pub fn x_twap_range(
+        e: Env,
+        base_asset: Asset,
+        quote_asset: Asset,
+        from_ts: u64,
+        to_ts: u64,
+    ) -> Option<i128> {
+        let asset_pair_indexes = get_asset_pair_indexes(&e, base_asset, quote_asset)?;
+        let decimals = e.get_decimals();
+        get_twap_range(
+            &e,
+            |timestamp| get_x_price_by_indexes(&e, asset_pair_indexes, timestamp, decimals),
+            from_ts,
+            to_ts,
+        )
+    }
+
+    // Returns open/high/low/close prices for the given asset over the given time range.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to quote
+    // * `from_ts` - Range start timestamp in seconds (inclusive)
+    // * `to_ts` - Range end timestamp in seconds (inclusive)
+    //
+    // # Returns
+    //
+    // OHLC data for the prices found in the range (up to 20 resolution steps back from the range end) or None if there were no records found
+    pub fn ohlc(e: Env, asset: Asset, from_ts: u64, to_ts: u64) -> Option<OHLCData> {
+        let asset_index = e.get_asset_index(&asset)?;
+        let (ohlc, _) = get_ohlc(
+            &e,
+            |timestamp| get_price_data_by_index(&e, asset_index, timestamp),
+            from_ts,
+            to_ts,
+        )?;
+        Some(ohlc)
+    }
+
+    // Returns open/high/low/close prices and the number of recorded prices for the given asset over the given time range.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to quote
+    // * `from_ts` - Range start timestamp in seconds (inclusive)
+    // * `to_ts` - Range end timestamp in seconds (inclusive)
+    //
+    // # Returns
+    //
+    // OHLCV data for the prices found in the range (up to 20 resolution steps back from the range end) or None if there were no records found
+    pub fn ohlcv(e: Env, asset: Asset, from_ts: u64, to_ts: u64) -> Option<OHLCVData> {
+        let asset_index = e.get_asset_index(&asset)?;
+        let (ohlc, record_count) = get_ohlc(
+            &e,
+            |timestamp| get_price_data_by_index(&e, asset_index, timestamp),
+            from_ts,
+            to_ts,
+        )?;
+        Some(OHLCVData {
+            open: ohlc.open,
+            high: ohlc.high,
+            low: ohlc.low,
+            close: ohlc.close,
+            timestamp_open: ohlc.timestamp_open,
+            timestamp_close: ohlc.timestamp_close,
+            record_count,
+        })
+    }
+
+    // Returns the weighted price of the assets basket at the last recorded timestamp.
+    //
+    // # Arguments
+    //
+    // * `assets` - Basket assets
+    // * `weights` - Asset weights in basis points, should sum up to 10000
+    //
+    // # Returns
+    //
+    // Weighted basket price or None if any of the assets is not supported or has no price
+    //
+    // # Panics
+    //
+    // Panics if the weights are invalid
+    pub fn basket_price(e: Env, assets: Vec<Asset>, weights: Vec<u32>) -> Option<i128> {
+        validate_weights(&e, &assets, &weights);
+        let timestamp = obtain_record_timestamp(&e);
+        if timestamp == 0 {
+            return None;
+        }
+        get_basket_price(&e, assets, weights, timestamp)
+    }
+
+    // Returns the weighted price of the assets basket quoted in the given asset at the last recorded timestamp.
+    //
+    // # Arguments
+    //
+    // * `base_assets` - Basket assets
+    // * `base_weights` - Asset weights in basis points, should sum up to 10000
+    // * `quote_asset` - Quote asset
+    //
+    // # Returns
+    //
+    // Weighted basket cross price (basket_price/quote_asset_price) or None if any of the assets is not supported or has no price
+    //
+    // # Panics
+    //
+    // Panics if the weights are invalid
+    pub fn x_basket_price(
+        e: Env,
+        base_assets: Vec<Asset>,
+        base_weights: Vec<u32>,
+        quote_asset: Asset,
+    ) -> Option<i128> {
+        validate_weights(&e, &base_assets, &base_weights);
+        let timestamp = obtain_record_timestamp(&e);
+        if timestamp == 0 {
+            return None;
+        }
+        let basket_price = get_basket_price(&e, base_assets, base_weights, timestamp)?;
+        let quote_price = get_price_data(&e, quote_asset, timestamp)?.price;
+        Some(basket_price.fixed_div_floor(quote_price, e.get_decimals()))
+    }
+
+    // Returns current protocol version of the contract.
+    //
+    // # Returns
+    //
+    // Contract protocol version
+    pub fn version(_e: Env) -> u32 {
+        env!("CARGO_PKG_VERSION")
+            .split(".")
+            .next()
+            .unwrap()
+            .parse::<u32>()
+            .unwrap()
+    }
+
+    // Returns the contract metadata in a single call.
+    //
+    // # Returns
+    //
+    // Base asset, decimals, resolution, retention period, number of assets, last timestamp and protocol version
+    pub fn oracle_info(e: Env) -> OracleInfo {
+        OracleInfo {
+            base: e.get_base_asset(),
+            decimals: e.get_decimals(),
+            resolution: Self::resolution(e.clone()),
+            period: Self::period(e.clone()),
+            asset_count: e.get_asset_count(),
+            last_timestamp: Self::last_timestamp(e.clone()),
+            version: Self::version(e),
+        }
+    }
+
+    // Returns current configuration protocol version of the contract.
+    //
+    // # Returns
+    //
+    // Configuration protocol version
+    pub fn config_version(e: Env) -> u32 {
+        e.get_config_version()
+    }
+
+    //Admin section
+
+    // Returns admin address of the contract.
+    //
+    // # Returns
+    //
+    // Contract admin account address
+    pub fn admin(e: Env) -> Option<Address> {
+        e.get_admin()
+    }
+
+    // Returns the previous admin addresses of the contract in chronological order. The current admin is not included.
+    //
+    // # Returns
+    //
+    // Up to 10 most recent previous admin addresses
+    pub fn admin_history(e: Env) -> Vec<Address> {
+        e.get_admin_history()
+    }
+
+    // Proposes the new admin of the contract. The new admin has to accept the role with `accept_admin`. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `new_admin` - Proposed admin account address
+    //
     // # Panics
     //
-    // Panics if the contract is already initialized, or if the version is invalid
+    // Panics if the caller doesn't match admin address
+    pub fn propose_admin(e: Env, new_admin: Address) {
+        e.panic_if_not_admin();
+        e.set_pending_admin(&new_admin);
+    }
+
+    // Accepts the admin role proposed with `propose_admin`. Can be invoked only by the proposed admin account.
+    //
+    // # Panics
+    //
+    // Panics if there is no proposed admin, or if the caller doesn't match the proposed admin address
+    pub fn accept_admin(e: Env) {
+        let new_admin = e
+            .get_pending_admin()
+            .unwrap_or_else(|| panic_with_error!(&e, Error::Unauthorized));
+        new_admin.require_auth();
+        e.set_admin(&new_admin);
+        e.clear_pending_admin();
+    }
+
+    // Migrates the storage from the layout version 1 to version 2 by writing the asset count computed from the assets list.
+    // Does nothing if the storage is already migrated. Can be invoked only by the admin account.
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address
+    pub fn migrate_v1_to_v2(e: Env) {
+        e.panic_if_not_admin();
+        if e.get_layout_version() != 1 {
+            return;
+        }
+        e.set_asset_count(e.get_assets().len());
+        e.set_layout_version(2);
+    }
+
+    // Updates the contract configuration parameters. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `admin` - Admin account address
+    // * `config` - Configuration parameters
+    //
+    // # Panics
+    //
+    // Panics if the contract is already initialized, or if the version, resolution, decimals value or period is invalid
     pub fn config(e: Env, config: ConfigData) {
+        if !is_valid_resolution(config.resolution) {
+            panic_with_error!(&e, Error::InvalidResolution);
+        }
+        if !is_valid_decimals(config.decimals) {
+            panic_with_error!(&e, Error::InvalidDecimalsValue);
+        }
+        if !is_valid_retention_period(config.period, config.resolution) {
+            panic_with_error!(&e, Error::RetentionPeriodTooShort);
+        }
         config.admin.require_auth();
         if e.is_initialized() {
             e.panic_with_error(Error::AlreadyInitialized);
@@ -299,88 +1058,801 @@ impl PriceOracleContract {
         e.set_decimals(config.decimals);
         e.set_resolution(config.resolution);
         e.set_retention_period(config.period);
+        e.set_config_version(config.version);
+        e.set_layout_version(STORAGE_LAYOUT_VERSION);
+
+        Self::__add_assets(&e, config.assets.clone());
+        events::publish_config_updated(&e, config);
+    }
+
+    // Validates the contract configuration parameters without applying them.
+    //
+    // # Arguments
+    //
+    // * `config` - Configuration parameters
+    //
+    // # Returns
+    //
+    // All validation errors found, empty if the configuration is valid
+    pub fn validate_config(e: Env, config: ConfigData) -> Vec<Error> {
+        let mut errors = Vec::new(&e);
+        if !is_valid_resolution(config.resolution) {
+            errors.push_back(Error::InvalidResolution);
+        }
+        if !is_valid_decimals(config.decimals) {
+            errors.push_back(Error::InvalidDecimalsValue);
+        }
+        if !is_valid_retention_period(config.period, config.resolution) {
+            errors.push_back(Error::RetentionPeriodTooShort);
+        }
+        if config.assets.contains(&config.base_asset) {
+            errors.push_back(Error::InvalidBaseAsset);
+        }
+        errors
+    }
+
+    // Adds given assets to the contract quoted assets list. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `admin` - Admin account address
+    // * `assets` - Assets to add
+    // * `version` - Configuration protocol version
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, if the assets are already added, if the version is invalid,
+    // or if the storage layout has to be migrated
+    pub fn add_assets(e: Env, assets: Vec<Asset>, version: u32) {
+        e.panic_if_not_admin();
+        panic_if_storage_migration_required(&e);
+        update_config_version(&e, version);
+        Self::__add_assets(&e, assets);
+    }
 
-        Self::__add_assets(&e, config.assets);
+    // Proposes the new history retention period for the prices. The period is applied with `execute_config_change` once the timelock expires,
+    // replacing the previously proposed change, if any. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `period` - History retention period (in milliseconds)
+    // * `version` - Configuration protocol version
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, or if the period/version is invalid
+    pub fn set_period(e: Env, period: u64, version: u32) {
+        e.panic_if_not_admin();
+        update_config_version(&e, version);
+        propose_config_change(
+            &e,
+            &ConfigChange {
+                change_type: ChangeType::Period,
+                new_value: period,
+            },
+        );
+    }
+
+    // Updates the base asset the prices are reported in. Can be invoked only by the admin account.
+    // Previously recorded prices remain denominated in the old base asset, so the config version is incremented
+    // and the event with both old and new base assets is published.
+    //
+    // # Arguments
+    //
+    // * `new_base` - New base asset
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address
+    pub fn update_base_asset(e: Env, new_base: Asset) {
+        e.panic_if_not_admin();
+        let old_base = e.get_base_asset();
+        e.set_base_asset(&new_base);
+        e.set_config_version(e.get_config_version() + 1);
+        events::publish_base_asset_updated(&e, old_base, new_base);
+    }
+
+    // Proposes the max allowed age of the last price record to be considered actual. The period is applied with `execute_config_change`
+    // once the timelock expires, replacing the previously proposed change, if any. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `period_seconds` - Last price grace period (in seconds)
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, or if the period is invalid
+    pub fn set_grace_period(e: Env, period_seconds: u64) {
+        e.panic_if_not_admin();
+        propose_config_change(
+            &e,
+            &ConfigChange {
+                change_type: ChangeType::GracePeriod,
+                new_value: period_seconds,
+            },
+        );
+    }
+
+    // Proposes the config change that can be executed with `execute_config_change` once the timelock expires.
+    // Replaces the previously proposed change, if any. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `change` - Config change to apply
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, or if the new value is invalid
+    pub fn propose_config_change(e: Env, change: ConfigChange) {
+        e.panic_if_not_admin();
+        propose_config_change(&e, &change);
+    }
+
+    // Applies the pending config change after the timelock expires. Can be invoked by any account.
+    //
+    // # Panics
+    //
+    // Panics if there is no pending change, if the timelock has not expired yet, or if the new value is invalid
+    pub fn execute_config_change(e: Env) {
+        let (change, unlock_timestamp) = e
+            .get_pending_config_change()
+            .unwrap_or_else(|| panic_with_error!(&e, Error::ConfigChangeNotFound));
+        if e.ledger().timestamp() < unlock_timestamp {
+            panic_with_error!(&e, Error::ConfigChangeLocked);
+        }
+        //the resolution could change since the proposal, so validate the change again
+        validate_config_change(&e, &change);
+        match change.change_type {
+            ChangeType::Period => {
+                e.set_retention_period(change.new_value);
+                events::publish_period_changed(&e, change.new_value);
+            }
+            ChangeType::GracePeriod => e.set_grace_period(change.new_value * 1000), //convert to milliseconds
+            ChangeType::Timelock => e.set_config_timelock(change.new_value),
+        }
+        e.set_config_version(e.get_config_version() + 1);
+        e.clear_pending_config_change();
+    }
+
+    // Cancels the pending config change. Can be invoked only by the admin account.
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, or if there is no pending change
+    pub fn cancel_config_change(e: Env) {
+        e.panic_if_not_admin();
+        if e.get_pending_config_change().is_none() {
+            panic_with_error!(&e, Error::ConfigChangeNotFound);
+        }
+        e.clear_pending_config_change();
+    }
+
+    // Returns the pending config change.
+    //
+    // # Returns
+    //
+    // Pending config change and the ledger timestamp (in seconds) it can be executed after, or None if there is no pending change
+    pub fn pending_config_change(e: Env) -> Option<(ConfigChange, u64)> {
+        e.get_pending_config_change()
+    }
+
+    // Returns the delay between proposing and executing a config change.
+    //
+    // # Returns
+    //
+    // Config change timelock (in seconds)
+    pub fn config_timelock(e: Env) -> u64 {
+        e.get_config_timelock()
+    }
+
+    // Returns the metadata configured for the asset.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to check
+    //
+    // # Returns
+    //
+    // Asset metadata or None if metadata is not set or the asset is not supported
+    pub fn get_asset_metadata(e: Env, asset: Asset) -> Option<AssetMetadata> {
+        let asset_index = e.get_asset_index(&asset)?;
+        e.get_asset_metadata(asset_index)
+    }
+
+    // Sets the metadata for the asset. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to describe
+    // * `metadata` - Asset metadata
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, if the asset is not supported, or if the decimals override is invalid
+    pub fn set_asset_metadata(e: Env, asset: Asset, metadata: AssetMetadata) {
+        e.panic_if_not_admin();
+        if let Some(decimals) = metadata.decimals_override {
+            if !is_valid_decimals(decimals) {
+                panic_with_error!(&e, Error::InvalidDecimalsValue);
+            }
+        }
+        let asset_index = get_asset_index_or_panic(&e, &asset);
+        e.set_asset_metadata(asset_index, &metadata);
+    }
+
+    // Returns the price bounds configured for the asset.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to check
+    //
+    // # Returns
+    //
+    // Min and max allowed prices for the asset or None if bounds are not set or the asset is not supported
+    pub fn get_price_bounds(e: Env, asset: Asset) -> Option<(i128, i128)> {
+        let asset_index = e.get_asset_index(&asset)?;
+        e.get_price_bounds(asset_index)
+    }
+
+    // Sets absolute price bounds for the asset. Prices outside the bounds are rejected by `set_price`. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to constrain
+    // * `min_price` - Min allowed price (inclusive)
+    // * `max_price` - Max allowed price (inclusive)
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, if the asset is not supported, or if the bounds are invalid
+    pub fn set_price_bounds(e: Env, asset: Asset, min_price: i128, max_price: i128) {
+        e.panic_if_not_admin();
+        if min_price <= 0 || min_price > max_price {
+            panic_with_error!(&e, Error::InvalidPriceBounds);
+        }
+        let asset_index = get_asset_index_or_panic(&e, &asset);
+        e.set_price_bounds(asset_index, min_price, max_price);
+    }
+
+    // Removes price bounds for the asset. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset to remove bounds for
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, or if the asset is not supported
+    pub fn clear_price_bounds(e: Env, asset: Asset) {
+        e.panic_if_not_admin();
+        let asset_index = get_asset_index_or_panic(&e, &asset);
+        e.clear_price_bounds(asset_index);
+    }
+
+    // Sets the price bounds violation behavior. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `strict` - If true, `set_price` panics on out-of-bounds prices, otherwise such prices are skipped
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address
+    pub fn set_strict_price_bounds(e: Env, strict: bool) {
+        e.panic_if_not_admin();
+        e.set_strict_price_bounds(strict);
+    }
+
+    // Record new price feed history snapshot. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `admin` - Admin account address
+    // * `updates` - Price feed snapshot
+    // * `timestamp` - History snapshot timestamp (in milliseconds)
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, if the price snapshot record is invalid, or if the storage layout has to be migrated
+    pub fn set_price(e: Env, updates: Vec<i128>, timestamp: u64) {
+        set_price_snapshot(&e, updates, timestamp, None);
+    }
+
+    // Record new price feed history snapshot with the confidence intervals. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `updates` - Price feed snapshot
+    // * `timestamp` - History snapshot timestamp (in milliseconds)
+    // * `confidence` - Confidence intervals, parallel to the updates (0 means no confidence)
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, if the confidence length doesn't match the updates,
+    // if the price snapshot record is invalid, or if the storage layout has to be migrated
+    pub fn set_price_with_confidence(
+        e: Env,
+        updates: Vec<i128>,
+        timestamp: u64,
+        confidence: Vec<i128>,
+    ) {
+        set_price_snapshot(&e, updates, timestamp, Some(confidence));
+    }
+
+    // Validates the price feed snapshot without recording it. Runs the same checks as `set_price` and collects
+    // all found issues instead of panicking on the first one.
+    //
+    // # Arguments
+    //
+    // * `updates` - Price feed snapshot
+    // * `timestamp` - History snapshot timestamp (in milliseconds)
+    //
+    // # Returns
+    //
+    // Asset index and error pairs for the found issues, empty if `set_price` would succeed. Snapshot-level issues (timestamp, length)
+    // are reported with the 255 index. Zero prices are skipped, and out-of-bounds prices are reported only if strict price bounds are enabled.
+    pub fn validate_price_update(e: Env, updates: Vec<i128>, timestamp: u64) -> Vec<(u32, Error)> {
+        let mut issues = Vec::new(&e);
+        if !is_valid_update_timestamp(&e, timestamp) {
+            issues.push_back((SNAPSHOT_ISSUE_INDEX, Error::InvalidTimestamp));
+        }
+        let updates_len = updates.len();
+        if updates_len == 0 || updates_len > e.get_asset_count() {
+            issues.push_back((SNAPSHOT_ISSUE_INDEX, Error::InvalidUpdateLength));
+            return issues;
+        }
+        //out-of-bounds prices are skipped unless strict price bounds are enabled
+        if !e.get_strict_price_bounds() {
+            return issues;
+        }
+        let price_bounds = e.get_all_price_bounds();
+        for (i, price) in updates.iter().enumerate() {
+            let asset = AssetId(i as u8);
+            //zero price means no price
+            if price != 0 && !is_price_in_bounds(&price_bounds, asset, price) {
+                issues.push_back((asset.0 as u32, Error::PriceOutOfBounds));
+            }
+        }
+        issues
+    }
+
+    // Record new price feed history snapshot rejecting replays of the same snapshot. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `updates` - Price feed snapshot
+    // * `timestamp` - History snapshot timestamp (in milliseconds)
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, if the same snapshot was already submitted for the timestamp,
+    // or if the price snapshot record is invalid
+    pub fn set_price_deduplicated(e: Env, updates: Vec<i128>, timestamp: u64) {
+        let hash = get_price_snapshot_hash(&e, &updates, timestamp);
+        if e.get_update_hash(timestamp) == Some(hash.clone()) {
+            panic_with_error!(&e, Error::DuplicateUpdate);
+        }
+        Self::set_price(e.clone(), updates, timestamp);
+        e.set_update_hash(timestamp, &hash, get_ledgers_to_live(&e));
+    }
+
+    // Record new prices for the given assets. Unknown assets are skipped. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `updates` - Asset price updates
+    // * `timestamp` - History snapshot timestamp (in milliseconds)
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, or if the price snapshot record is invalid
+    pub fn set_price_named(e: Env, updates: Vec<PriceUpdateItem>, timestamp: u64) {
+        e.panic_if_not_admin();
+        let updates_len = updates.len();
+        if updates_len == 0 || updates_len > MAX_ASSETS {
+            panic_with_error!(&e, Error::InvalidUpdateLength);
+        }
+        record_prices(
+            &e,
+            updates.iter().filter_map(|update| {
+                let asset_index = e.get_asset_index(&update.asset);
+                if asset_index.is_none() {
+                    log!(&e, "Unknown asset skipped", update.asset);
+                    return None;
+                }
+                Some((asset_index.unwrap(), update.price, 0))
+            }),
+            timestamp,
+            e.get_admin().as_ref(),
+        );
+    }
+
+    // Returns the ed25519 public keys allowed to sign price snapshots submitted with `set_price_signed`.
+    //
+    // # Returns
+    //
+    // Authorized signer public keys
+    pub fn signers(e: Env) -> Vec<BytesN<32>> {
+        e.get_signers()
+    }
+
+    // Sets the ed25519 public keys allowed to sign price snapshots. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `signers` - Authorized signer public keys
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address
+    pub fn set_signers(e: Env, signers: Vec<BytesN<32>>) {
+        e.panic_if_not_admin();
+        e.set_signers(&signers);
+    }
+
+    // Record new price feed history snapshot signed off-chain by an authorized signer. Can be relayed by any account.
+    // The signed message is sha256 of the `reflector:set_price_signed` domain tag, the XDR-encoded contract address,
+    // the concatenated big-endian price updates (16 bytes each), and the big-endian timestamp (8 bytes).
+    // Each signed message is accepted only once.
+    //
+    // # Arguments
+    //
+    // * `updates` - Price feed snapshot
+    // * `timestamp` - History snapshot timestamp (in milliseconds)
+    // * `signer` - Signer ed25519 public key. Ed25519 doesn't support public key recovery, so the key is passed explicitly
+    // * `signature` - Ed25519 signature of the snapshot hash
+    //
+    // # Panics
+    //
+    // Panics if the signer is not authorized, if the signature is invalid, if the message was already submitted,
+    // or if the price snapshot record is invalid
+    pub fn set_price_signed(
+        e: Env,
+        updates: Vec<i128>,
+        timestamp: u64,
+        signer: BytesN<32>,
+        signature: BytesN<64>,
+    ) {
+        if !e.get_signers().contains(&signer) {
+            panic_with_error!(&e, Error::Unauthorized);
+        }
+        let updates_len = updates.len();
+        if updates_len == 0 || updates_len > e.get_asset_count() {
+            panic_with_error!(&e, Error::InvalidUpdateLength);
+        }
+        let message = get_signed_snapshot_message(&e, &updates, timestamp);
+        if e.is_signed_update_consumed(&message) {
+            panic_with_error!(&e, Error::DuplicateUpdate);
+        }
+        e.crypto()
+            .ed25519_verify(&signer, &message.clone().into(), &signature);
+        e.set_signed_update_consumed(&message, get_ledgers_to_live(&e));
+        record_prices(
+            &e,
+            updates
+                .iter()
+                .enumerate()
+                .map(|(i, price)| (AssetId(i as u8), price, 0)),
+            timestamp,
+            None,
+        );
+    }
+
+    // Record new prices in the Pyth Network format. Prices and confidence intervals are converted to the contract decimals.
+    // Unknown assets and stale updates published more than one resolution before the snapshot timestamp are skipped.
+    // Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `updates` - Pyth price updates
+    // * `timestamp` - History snapshot timestamp (in milliseconds)
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, if the price snapshot record is invalid, or if a converted price overflows
+    pub fn set_price_pyth(e: Env, updates: Vec<PythPriceUpdate>, timestamp: u64) {
+        e.panic_if_not_admin();
+        let updates_len = updates.len();
+        if updates_len == 0 || updates_len > MAX_ASSETS {
+            panic_with_error!(&e, Error::InvalidUpdateLength);
+        }
+        let decimals = e.get_decimals() as i32;
+        let resolution: u64 = e.get_resolution().into();
+        record_prices(
+            &e,
+            updates.iter().filter_map(|update| {
+                let asset_index = e.get_asset_index(&update.asset);
+                if asset_index.is_none() {
+                    log!(&e, "Unknown asset skipped", update.asset);
+                    return None;
+                }
+                //convert to milliseconds
+                if timestamp.saturating_sub(update.publish_time.saturating_mul(1000)) > resolution {
+                    log!(
+                        &e,
+                        "Stale update skipped",
+                        update.asset,
+                        update.publish_time
+                    );
+                    return None;
+                }
+                let shift = decimals + update.exponent;
+                let price = convert_pyth_value(&e, update.price as i128, shift);
+                let confidence = convert_pyth_value(&e, update.confidence as i128, shift);
+                Some((asset_index.unwrap(), price, confidence))
+            }),
+            timestamp,
+            e.get_admin().as_ref(),
+        );
+    }
+
+    // Returns the list of accounts allowed to submit prices to the price rounds.
+    //
+    // # Returns
+    //
+    // Authorized submitters
+    pub fn submitters(e: Env) -> Vec<Address> {
+        e.get_submitters()
+    }
+
+    // Sets the list of accounts allowed to submit prices to the price rounds. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `submitters` - Authorized submitters
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address
+    pub fn set_submitters(e: Env, submitters: Vec<Address>) {
+        e.panic_if_not_admin();
+        e.set_submitters(&submitters);
+    }
+
+    // Returns the min number of submissions required to close a price round.
+    //
+    // # Returns
+    //
+    // Min quorum
+    pub fn min_quorum(e: Env) -> u32 {
+        e.get_min_quorum()
+    }
+
+    // Sets the min number of submissions required to close a price round. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `quorum` - Min quorum
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, or if the quorum is 0
+    pub fn set_min_quorum(e: Env, quorum: u32) {
+        e.panic_if_not_admin();
+        if quorum == 0 {
+            panic_with_error!(&e, Error::InvalidQuorum);
+        }
+        e.set_min_quorum(quorum);
+    }
+
+    // Opens a new price round collecting prices for the given history snapshot timestamp.
+    //
+    // # Arguments
+    //
+    // * `submitter` - Submitter account address
+    // * `round_id` - Round identifier
+    // * `timestamp` - History snapshot timestamp (in milliseconds)
+    //
+    // # Panics
+    //
+    // Panics if the caller is not an authorized submitter, or if the round is already open
+    pub fn open_round(e: Env, submitter: Address, round_id: u64, timestamp: u64) {
+        panic_if_not_submitter(&e, &submitter);
+        if e.get_price_round(round_id).is_some() {
+            panic_with_error!(&e, Error::RoundAlreadyExists);
+        }
+        let round = PriceRound {
+            timestamp,
+            opened_by: submitter,
+            submissions: Map::new(&e),
+        };
+        e.set_price_round(round_id, &round, get_ledgers_to_live(&e));
+    }
+
+    // Records the submitter's price feed snapshot for the open price round. Repeated submissions replace the previous one.
+    //
+    // # Arguments
+    //
+    // * `submitter` - Submitter account address
+    // * `round_id` - Round identifier
+    // * `updates` - Price feed snapshot
+    //
+    // # Panics
+    //
+    // Panics if the caller is not an authorized submitter, if the round is not found, or if the updates length is invalid
+    pub fn submit_prices(e: Env, submitter: Address, round_id: u64, updates: Vec<i128>) {
+        panic_if_not_submitter(&e, &submitter);
+        let updates_len = updates.len();
+        if updates_len == 0 || updates_len > e.get_asset_count() {
+            panic_with_error!(&e, Error::InvalidUpdateLength);
+        }
+        let mut round = e
+            .get_price_round(round_id)
+            .unwrap_or_else(|| panic_with_error!(&e, Error::RoundNotFound));
+        round.submissions.set(submitter, updates);
+        e.set_price_round(round_id, &round, get_ledgers_to_live(&e));
+    }
+
+    // Closes the price round and records the median of the submitted prices for each asset. Can be invoked only by the admin or an authorized submitter.
+    // Submissions of accounts removed from the submitters list are ignored.
+    //
+    // # Arguments
+    //
+    // * `caller` - Admin or submitter account address
+    // * `round_id` - Round identifier
+    //
+    // # Panics
+    //
+    // Panics if the caller is neither the admin nor an authorized submitter, if the round is not found,
+    // if the round has fewer submissions than the min quorum, or if the round timestamp is invalid
+    pub fn close_round(e: Env, caller: Address, round_id: u64) {
+        caller.require_auth();
+        let submitters = e.get_submitters();
+        if e.get_admin() != Some(caller.clone()) && !submitters.contains(&caller) {
+            panic_with_error!(&e, Error::Unauthorized);
+        }
+        let round = e
+            .get_price_round(round_id)
+            .unwrap_or_else(|| panic_with_error!(&e, Error::RoundNotFound));
+        let mut submissions = Vec::new(&e);
+        for (submitter, updates) in round.submissions.iter() {
+            if submitters.contains(&submitter) {
+                submissions.push_back(updates);
+            }
+        }
+        if submissions.len() < e.get_min_quorum() {
+            panic_with_error!(&e, Error::QuorumNotReached);
+        }
+        let updates_len = submissions.iter().map(|updates| updates.len()).max();
+        let mut medians = Vec::new(&e);
+        for asset in 0..updates_len.unwrap_or_default() {
+            let mut prices = Vec::new(&e);
+            for updates in submissions.iter() {
+                let price = updates.get(asset).unwrap_or_default();
+                //zero price means no price
+                if price != 0 {
+                    prices.push_back(price);
+                }
+            }
+            medians.push_back(get_median(prices).unwrap_or_default());
+        }
+        e.remove_price_round(round_id);
+        record_prices(
+            &e,
+            medians
+                .iter()
+                .enumerate()
+                .map(|(i, price)| (AssetId(i as u8), price, 0)),
+            round.timestamp,
+            Some(&round.opened_by),
+        );
+    }
+
+    // Extends the lifetime of the price record, so it stays available after the retention period. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `asset` - Asset of the price record
+    // * `timestamp` - Timestamp of the price record in seconds
+    // * `additional_ledgers` - Number of ledgers the record should live for from the current ledger
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address, if the asset is not supported, or if the price record doesn't exist
+    pub fn bump_price(e: Env, asset: Asset, timestamp: u64, additional_ledgers: u32) {
+        e.panic_if_not_admin();
+        let asset_index = get_asset_index_or_panic(&e, &asset);
+        let normalized_timestamp = //convert to milliseconds and normalize
+            (timestamp * 1000).get_normalized_timestamp(e.get_resolution().into());
+        if !e.extend_price_ttl(asset_index, normalized_timestamp, additional_ledgers) {
+            panic_with_error!(&e, Error::PriceNotFound);
+        }
     }
 
-    // Adds given assets to the contract quoted assets list. Can be invoked only by the admin account.
+    // Extends the lifetime of the stored price records to match the current retention period. Should be invoked after the retention period increase,
+    // walking back from the last timestamp in 20-step pages for each page of assets. Can be invoked only by the admin account.
     //
     // # Arguments
     //
-    // * `admin` - Admin account address
-    // * `assets` - Assets to add
-    // * `version` - Configuration protocol version
+    // * `from_ts` - Timestamp in seconds, records at and before it are extended (up to 20 resolution steps)
+    // * `offset` - Index of the first asset
+    // * `limit` - Number of assets to process (max 5)
+    //
+    // # Returns
+    //
+    // Number of price records extended
     //
     // # Panics
     //
-    // Panics if the caller doesn't match admin address, or if the assets are already added
-    pub fn add_assets(e: Env, assets: Vec<Asset>) {
+    // Panics if the caller doesn't match admin address
+    pub fn refresh_retention(e: Env, from_ts: u64, offset: u32, limit: u32) -> u32 {
         e.panic_if_not_admin();
-        Self::__add_assets(&e, assets);
+        let ledgers_to_live = get_ledgers_to_live(&e);
+        //convert to milliseconds and normalize
+        let from_timestamp = from_ts
+            .saturating_mul(1000)
+            .get_normalized_timestamp(e.get_resolution().into());
+        let end = offset
+            .saturating_add(limit.min(MAX_MAINTENANCE_ASSETS))
+            .min(e.get_asset_count());
+        let mut bumped = 0;
+        for asset in offset..end {
+            bumped += e.bump_all_prices(
+                AssetId(asset as u8),
+                from_timestamp,
+                MAX_REFRESH_RECORDS,
+                ledgers_to_live,
+            );
+        }
+        bumped
     }
 
-    // Sets history retention period for the prices. Can be invoked only by the admin account.
+    // Removes stored price records of the asset older than the given timestamp. Can be invoked only by the admin account.
     //
     // # Arguments
     //
-    // * `admin` - Admin account address
-    // * `period` - History retention period (in seconds)
-    // * `version` - Configuration protocol version
+    // * `asset` - Asset to clean up
+    // * `before_ts` - Timestamp in seconds, records before it are removed (up to 20 resolution steps)
+    //
+    // # Returns
+    //
+    // Cleanup stats
     //
     // # Panics
     //
-    // Panics if the caller doesn't match admin address, or if the period/version is invalid
-    pub fn set_period(e: Env, period: u64) {
+    // Panics if the caller doesn't match admin address, or if the asset is not supported
+    pub fn prune_prices(e: Env, asset: Asset, before_ts: u64) -> PruneResult {
         e.panic_if_not_admin();
-        e.set_retention_period(period);
+        let asset_index = get_asset_index_or_panic(&e, &asset);
+        let records_pruned = prune_asset_prices(&e, asset_index, before_ts);
+        PruneResult {
+            assets_pruned: if records_pruned > 0 { 1 } else { 0 },
+            records_pruned,
+        }
     }
 
-    // Record new price feed history snapshot. Can be invoked only by the admin account.
+    // Removes stored price records of the page of assets older than the given timestamp. Can be invoked only by the admin account.
     //
     // # Arguments
     //
-    // * `admin` - Admin account address
-    // * `updates` - Price feed snapshot
-    // * `timestamp` - History snapshot timestamp
+    // * `before_ts` - Timestamp in seconds, records before it are removed (up to 20 resolution steps)
+    // * `offset` - Index of the first asset
+    // * `limit` - Number of assets to process (max 5)
+    //
+    // # Returns
+    //
+    // Cleanup stats
     //
     // # Panics
     //
-    // Panics if the caller doesn't match admin address, or if the price snapshot record is invalid
-    pub fn set_price(e: Env, updates: Vec<i128>, timestamp: u64) {
+    // Panics if the caller doesn't match admin address
+    pub fn prune_all_prices(e: Env, before_ts: u64, offset: u32, limit: u32) -> PruneResult {
         e.panic_if_not_admin();
-        let updates_len = updates.len();
-        if updates_len == 0 || updates_len >= 256 {
-            panic_with_error!(&e, Error::InvalidUpdateLength);
-        }
-        let timeframe: u64 = e.get_resolution().into();
-        let ledger_timestamp = now(&e);
-        if timestamp == 0
-            || !timestamp.is_valid_timestamp(timeframe)
-            || timestamp > ledger_timestamp
-        {
-            panic_with_error!(&e, Error::InvalidTimestamp);
-        }
-
-        let retention_period = e.get_retention_period();
-
-        let ledgers_to_live: u32 = ((retention_period / 1000 / 5) + 1) as u32;
-
-        //get the last timestamp
-        let last_timestamp = e.get_last_timestamp();
-
-        //iterate over the updates
-        for (i, price) in updates.iter().enumerate() {
-            //don't store zero prices
-            if price == 0 {
-                continue;
+        let mut result = PruneResult {
+            assets_pruned: 0,
+            records_pruned: 0,
+        };
+        let end = offset
+            .saturating_add(limit.min(MAX_MAINTENANCE_ASSETS))
+            .min(e.get_asset_count());
+        for asset_index in offset..end {
+            let records_pruned = prune_asset_prices(&e, AssetId(asset_index as u8), before_ts);
+            if records_pruned > 0 {
+                result.assets_pruned += 1;
+                result.records_pruned += records_pruned;
             }
-            let asset = i as u8;
-            //store the new price
-            e.set_price(asset, price, timestamp, ledgers_to_live);
-        }
-        if timestamp > last_timestamp {
-            e.set_last_timestamp(timestamp);
         }
+        result
     }
 
     // Updates the contract source code. Can be invoked only by the admin account.
@@ -392,12 +1864,70 @@ impl PriceOracleContract {
     //
     // # Panics
     //
-    // Panics if the caller doesn't match admin address
+    // Panics if the caller doesn't match admin address, or if the minimal delay since the last update hasn't elapsed yet
     pub fn update_contract(env: Env, wasm_hash: BytesN<32>) {
         env.panic_if_not_admin();
+        if !is_upgrade_allowed(&env) {
+            panic_with_error!(&env, Error::UpgradeTooEarly);
+        }
+        env.set_wasm_hash(&wasm_hash);
+        env.set_last_upgrade_timestamp(env.ledger().timestamp());
         env.deployer().update_current_contract_wasm(wasm_hash)
     }
 
+    // Returns the min delay between contract updates.
+    //
+    // # Returns
+    //
+    // Upgrade delay (in seconds), 0 if not set
+    pub fn upgrade_delay(e: Env) -> u64 {
+        e.get_upgrade_delay()
+    }
+
+    // Sets the min delay between contract updates. Setting it to 0 allows immediate updates. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `delay_seconds` - Upgrade delay (in seconds)
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address
+    pub fn set_upgrade_delay(e: Env, delay_seconds: u64) {
+        e.panic_if_not_admin();
+        e.set_upgrade_delay(delay_seconds);
+    }
+
+    // Checks whether the contract can be updated with the given WASM now. Can be invoked only by the admin account.
+    //
+    // # Arguments
+    //
+    // * `wasm_hash` - WASM hash of the contract source code
+    //
+    // # Returns
+    //
+    // True if the upgrade delay set by the admin has elapsed since the last contract update
+    //
+    // # Panics
+    //
+    // Panics if the caller doesn't match admin address
+    pub fn validate_upgrade(e: Env, wasm_hash: BytesN<32>) -> bool {
+        e.panic_if_not_admin();
+        //the hash length is enforced by the BytesN<32> type
+        let _ = wasm_hash;
+        is_upgrade_allowed(&e)
+    }
+
+    // Returns the WASM hash set by the last contract update. The SDK doesn't expose the hash of the running contract code,
+    // so the hash is recorded by `update_contract`.
+    //
+    // # Returns
+    //
+    // WASM hash of the contract source code or None if the contract has never been updated
+    pub fn wasm_hash(e: Env) -> Option<BytesN<32>> {
+        e.get_wasm_hash()
+    }
+
     fn __add_assets(e: &Env, assets: Vec<Asset>) {
         let mut current_assets = e.get_assets();
         for asset in assets.iter() {
@@ -406,15 +1936,286 @@ impl PriceOracleContract {
                 panic_with_error!(&e, Error::AssetAlreadyExists);
             }
             e.set_asset_index(&asset, current_assets.len());
+            events::publish_asset_added(e, asset.clone(), current_assets.len());
             current_assets.push_back(asset);
         }
-        if current_assets.len() >= 256 {
+        if e.get_asset_count() > MAX_ASSETS {
             panic_with_error!(&e, Error::AssetLimitExceeded);
         }
         e.set_assets(current_assets);
     }
 }
 
+impl OracleInterface for PriceOracleContract {
+    fn base(e: &Env) -> Asset {
+        Self::base(e.clone())
+    }
+
+    fn decimals(e: &Env) -> u32 {
+        Self::decimals(e.clone())
+    }
+
+    fn resolution(e: &Env) -> u32 {
+        Self::resolution(e.clone())
+    }
+
+    fn period(e: &Env) -> Option<u64> {
+        Self::period(e.clone())
+    }
+
+    fn assets(e: &Env) -> Vec<Asset> {
+        Self::assets(e.clone())
+    }
+
+    fn last_timestamp(e: &Env) -> u64 {
+        Self::last_timestamp(e.clone())
+    }
+
+    fn price(e: &Env, asset: Asset, timestamp: u64) -> Option<PriceData> {
+        Self::price(e.clone(), asset, timestamp)
+    }
+
+    fn lastprice(e: &Env, asset: Asset) -> Option<PriceData> {
+        Self::lastprice(e.clone(), asset)
+    }
+
+    fn prices(e: &Env, asset: Asset, records: u32) -> Option<Vec<PriceData>> {
+        Self::prices(e.clone(), asset, records)
+    }
+
+    fn x_last_price(e: &Env, base_asset: Asset, quote_asset: Asset) -> Option<PriceData> {
+        Self::x_last_price(e.clone(), base_asset, quote_asset)
+    }
+
+    fn x_price(
+        e: &Env,
+        base_asset: Asset,
+        quote_asset: Asset,
+        timestamp: u64,
+    ) -> Option<PriceData> {
+        Self::x_price(e.clone(), base_asset, quote_asset, timestamp)
+    }
+
+    fn x_prices(
+        e: &Env,
+        base_asset: Asset,
+        quote_asset: Asset,
+        records: u32,
+    ) -> Option<Vec<PriceData>> {
+        Self::x_prices(e.clone(), base_asset, quote_asset, records)
+    }
+
+    fn twap(e: &Env, asset: Asset, records: u32) -> Option<i128> {
+        Self::twap(e.clone(), asset, records)
+    }
+
+    fn x_twap(e: &Env, base_asset: Asset, quote_asset: Asset, records: u32) -> Option<i128> {
+        Self::x_twap(e.clone(), base_asset, quote_asset, records)
+    }
+
+    fn version(e: &Env) -> u32 {
+        Self::version(e.clone())
+    }
+}
+
+fn set_price_snapshot(e: &Env, updates: Vec<i128>, timestamp: u64, confidence: Option<Vec<i128>>) {
+    e.panic_if_not_admin();
+    let updates_len = updates.len();
+    //the asset count never exceeds the assets limit
+    if updates_len == 0 || updates_len > e.get_asset_count() {
+        panic_with_error!(e, Error::InvalidUpdateLength);
+    }
+    if let Some(confidence) = &confidence {
+        if confidence.len() != updates_len {
+            panic_with_error!(e, Error::InvalidUpdateLength);
+        }
+    }
+    record_prices(
+        e,
+        updates.iter().enumerate().map(|(i, price)| {
+            let price_confidence = confidence
+                .as_ref()
+                .map_or(0, |confidence| confidence.get_unchecked(i as u32));
+            (AssetId(i as u8), price, price_confidence)
+        }),
+        timestamp,
+        e.get_admin().as_ref(),
+    );
+}
+
+fn record_prices<I: Iterator<Item = (AssetId, i128, i128)>>(
+    e: &Env,
+    updates: I,
+    timestamp: u64,
+    source: Option<&Address>,
+) {
+    panic_if_storage_migration_required(e);
+    if !is_valid_update_timestamp(e, timestamp) {
+        panic_with_error!(e, Error::InvalidTimestamp);
+    }
+
+    let ledgers_to_live = get_ledgers_to_live(e);
+
+    //get the last timestamp
+    let last_timestamp = e.get_last_timestamp();
+
+    let strict_bounds = e.get_strict_price_bounds();
+    //load all bounds with a single instance storage read
+    let price_bounds = e.get_all_price_bounds();
+
+    //iterate over the updates
+    for (asset, price, confidence) in updates {
+        //don't store zero prices
+        if price == 0 {
+            continue;
+        }
+        //check the price bounds
+        if !is_price_in_bounds(&price_bounds, asset, price) {
+            if strict_bounds {
+                panic_with_error!(e, Error::PriceOutOfBounds);
+            }
+            let old_price = e.get_price(asset, last_timestamp).unwrap_or_default();
+            events::publish_price_rejected(
+                e,
+                asset,
+                price,
+                old_price,
+                events::REJECTED_PRICE_OUT_OF_BOUNDS,
+            );
+            continue;
+        }
+        //store the new price
+        e.set_price(asset, price, timestamp, ledgers_to_live);
+        if confidence != 0 {
+            e.set_price_confidence(asset, confidence, timestamp, ledgers_to_live);
+        } else if timestamp <= last_timestamp {
+            //the overwritten price record may have a confidence stored
+            e.remove_price_confidence(asset, timestamp);
+        }
+        events::publish_price_update(e, asset, price, timestamp / 1000);
+    }
+    if timestamp > last_timestamp {
+        e.set_last_timestamp(timestamp);
+        e.set_last_update_source(source);
+        update_uptime_stats(e, timestamp, last_timestamp);
+    }
+}
+
+fn update_uptime_stats(e: &Env, timestamp: u64, last_timestamp: u64) {
+    let mut stats = e.get_uptime_stats();
+    let resolution: u64 = e.get_resolution().into();
+    if last_timestamp > 0 {
+        let missed_intervals = (timestamp - last_timestamp) / resolution - 1;
+        if missed_intervals == 0 {
+            stats.consecutive_missed_intervals = 0;
+        } else {
+            stats.consecutive_missed_intervals = stats
+                .consecutive_missed_intervals
+                .saturating_add(missed_intervals.try_into().unwrap_or(u32::MAX));
+        }
+    }
+    stats.total_updates += 1;
+    stats.last_update_timestamp = timestamp / 1000; //convert to seconds
+    e.set_uptime_stats(&stats);
+}
+
+fn is_upgrade_allowed(e: &Env) -> bool {
+    match e.get_last_upgrade_timestamp() {
+        Some(last_upgrade) => {
+            e.ledger().timestamp().saturating_sub(last_upgrade) >= e.get_upgrade_delay()
+        }
+        None => true,
+    }
+}
+
+fn panic_if_storage_migration_required(e: &Env) {
+    if e.get_layout_version() != STORAGE_LAYOUT_VERSION {
+        panic_with_error!(e, Error::StorageMigrationRequired);
+    }
+}
+
+fn is_valid_update_timestamp(e: &Env, timestamp: u64) -> bool {
+    let timeframe: u64 = e.get_resolution().into();
+    timestamp != 0 && timestamp.is_valid_timestamp(timeframe) && timestamp <= now(e)
+}
+
+fn is_price_in_bounds(bounds: &Map<u32, (i128, i128)>, asset: AssetId, price: i128) -> bool {
+    match bounds.get(asset.0 as u32) {
+        Some((min_price, max_price)) => price >= min_price && price <= max_price,
+        None => true,
+    }
+}
+
+fn get_price_snapshot_hash(e: &Env, updates: &Vec<i128>, timestamp: u64) -> BytesN<32> {
+    let mut data = Bytes::new(e);
+    for price in updates.iter() {
+        data.extend_from_array(&price.to_be_bytes());
+    }
+    data.extend_from_array(&timestamp.to_be_bytes());
+    e.crypto().sha256(&data)
+}
+
+fn get_signed_snapshot_message(e: &Env, updates: &Vec<i128>, timestamp: u64) -> BytesN<32> {
+    //bind the message to the entry point and the contract instance
+    let mut data = Bytes::from_slice(e, SIGNED_UPDATE_DOMAIN);
+    data.append(&e.current_contract_address().to_xdr(e));
+    for price in updates.iter() {
+        data.extend_from_array(&price.to_be_bytes());
+    }
+    data.extend_from_array(&timestamp.to_be_bytes());
+    e.crypto().sha256(&data)
+}
+
+fn convert_pyth_value(e: &Env, value: i128, shift: i32) -> i128 {
+    //positive shift moves the value left, negative shift moves it right
+    let factor = 10i128
+        .checked_pow(shift.unsigned_abs())
+        .unwrap_or_else(|| panic_with_error!(e, Error::PriceConversionOverflow));
+    if shift >= 0 {
+        value
+            .checked_mul(factor)
+            .unwrap_or_else(|| panic_with_error!(e, Error::PriceConversionOverflow))
+    } else {
+        value / factor
+    }
+}
+
+fn panic_if_not_submitter(e: &Env, submitter: &Address) {
+    submitter.require_auth();
+    if !e.get_submitters().contains(submitter) {
+        panic_with_error!(e, Error::Unauthorized);
+    }
+}
+
+fn get_median(prices: Vec<i128>) -> Option<i128> {
+    //insertion sort, the number of submissions is small
+    let mut sorted = Vec::new(prices.env());
+    for price in prices.iter() {
+        let position = sorted
+            .iter()
+            .position(|p| p > price)
+            .unwrap_or(sorted.len() as usize);
+        sorted.insert(position as u32, price);
+    }
+    let len = sorted.len();
+    if len == 0 {
+        return None;
+    }
+    let mid = sorted.get_unchecked(len / 2);
+    if len % 2 == 1 {
+        return Some(mid);
+    }
+    let lower = sorted.get_unchecked(len / 2 - 1);
+    //average the middle values avoiding overflow
+    Some(lower / 2 + mid / 2 + (lower % 2 + mid % 2) / 2)
+}
+
+fn get_ledgers_to_live(e: &Env) -> u32 {
+    let retention_period = e.get_retention_period();
+    //ledgers are closed every 5 seconds
+    ((retention_period / 1000 / 5) + 1) as u32
+}
+
 fn prices<F: Fn(u64) -> Option<PriceData>>(
     e: &Env,
     get_price_fn: F,
@@ -429,8 +2230,8 @@ fn prices<F: Fn(u64) -> Option<PriceData>>(
     let mut prices = Vec::new(e);
     let resolution = e.get_resolution() as u64;
 
-    // Limit the number of records to 20
-    records = records.min(20);
+    // Limit the number of records
+    records = records.min(MAX_RECORDS);
 
     while records > 0 {
         if let Some(price) = get_price_fn(timestamp) {
@@ -453,6 +2254,32 @@ fn prices<F: Fn(u64) -> Option<PriceData>>(
     }
 }
 
+fn prune_asset_prices(e: &Env, asset: AssetId, before_ts: u64) -> u32 {
+    let resolution = e.get_resolution() as u64;
+    let before_timestamp = before_ts.saturating_mul(1000); //convert to milliseconds
+    let mut timestamp = before_timestamp.get_normalized_timestamp(resolution);
+    if timestamp == before_timestamp {
+        //the record at the given timestamp is preserved
+        if timestamp < resolution {
+            return 0;
+        }
+        timestamp -= resolution;
+    }
+    let mut records = MAX_PRUNE_RECORDS;
+    let mut pruned = 0;
+    while records > 0 && timestamp > 0 {
+        if e.remove_price(asset, timestamp) {
+            pruned += 1;
+        }
+        records -= 1;
+        if timestamp < resolution {
+            break;
+        }
+        timestamp -= resolution;
+    }
+    pruned
+}
+
 fn now(e: &Env) -> u64 {
     e.ledger().timestamp() * 1000 //convert to milliseconds
 }
@@ -460,11 +2287,10 @@ fn now(e: &Env) -> u64 {
 fn obtain_record_timestamp(e: &Env) -> u64 {
     let last_timestamp = e.get_last_timestamp();
     let ledger_timestamp = now(&e);
-    let resolution = e.get_resolution() as u64;
+    let grace_period = e.get_grace_period();
     if last_timestamp == 0 //no prices yet
-        || last_timestamp > ledger_timestamp //last timestamp is in the future
-        || ledger_timestamp - last_timestamp >= resolution * 2
-    //last timestamp is too far in the past, so we cannot return the last price
+        //last timestamp is in the future or too far in the past, so we cannot return the last price
+        || !last_timestamp.is_within_window(ledger_timestamp, grace_period)
     {
         return 0;
     }
@@ -495,6 +2321,152 @@ fn get_twap<F: Fn(u64) -> Option<PriceData>>(
     Some(sum / prices.len() as i128)
 }
 
+fn prices_in_range<F: Fn(u64) -> Option<PriceData>>(
+    e: &Env,
+    get_price_fn: F,
+    from_ts: u64,
+    to_ts: u64,
+) -> Option<Vec<PriceData>> {
+    let mut prices = Vec::new(e);
+    if from_ts > to_ts {
+        return Some(prices);
+    }
+    let resolution = e.get_resolution() as u64;
+    //convert to milliseconds and normalize, there are no records after the last timestamp
+    let to_timestamp = to_ts
+        .checked_mul(1000)?
+        .min(e.get_last_timestamp())
+        .get_normalized_timestamp(resolution);
+    let mut from_timestamp = from_ts
+        .checked_mul(1000)?
+        .get_normalized_timestamp(resolution);
+    //records older than the retention period are expired
+    let retention_start = to_timestamp.saturating_sub(e.get_retention_period());
+    if from_timestamp < retention_start {
+        from_timestamp = retention_start.get_normalized_timestamp(resolution);
+    }
+
+    //walk back from the range end, limiting the number of records
+    let mut timestamp = to_timestamp;
+    let mut records = MAX_RECORDS;
+    while records > 0 && timestamp >= from_timestamp {
+        if let Some(price) = get_price_fn(timestamp) {
+            prices.push_front(price);
+        }
+        records -= 1;
+        if timestamp < resolution {
+            break;
+        }
+        timestamp -= resolution;
+    }
+    Some(prices)
+}
+
+fn get_twap_range<F: Fn(u64) -> Option<PriceData>>(
+    e: &Env,
+    get_price_fn: F,
+    from_ts: u64,
+    to_ts: u64,
+) -> Option<i128> {
+    let prices = prices_in_range(e, get_price_fn, from_ts, to_ts)?;
+    if prices.is_empty() {
+        return None;
+    }
+    let sum: i128 = prices.iter().map(|price_data| price_data.price).sum();
+    Some(sum / prices.len() as i128)
+}
+
+fn get_ohlc<F: Fn(u64) -> Option<PriceData>>(
+    e: &Env,
+    get_price_fn: F,
+    from_ts: u64,
+    to_ts: u64,
+) -> Option<(OHLCData, u32)> {
+    let prices = prices_in_range(e, get_price_fn, from_ts, to_ts)?;
+    let first = prices.first()?;
+    let last = prices.last()?;
+    let mut ohlc = OHLCData {
+        open: first.price,
+        high: first.price,
+        low: first.price,
+        close: last.price,
+        timestamp_open: first.timestamp,
+        timestamp_close: last.timestamp,
+    };
+    for price_data in prices.iter() {
+        ohlc.high = ohlc.high.max(price_data.price);
+        ohlc.low = ohlc.low.min(price_data.price);
+    }
+    Some((ohlc, prices.len()))
+}
+
+fn propose_config_change(e: &Env, change: &ConfigChange) {
+    validate_config_change(e, change);
+    let unlock_timestamp = e.ledger().timestamp() + e.get_config_timelock();
+    e.set_pending_config_change(change, unlock_timestamp);
+}
+
+fn validate_config_change(e: &Env, change: &ConfigChange) {
+    match change.change_type {
+        ChangeType::Period => {
+            if !is_valid_retention_period(change.new_value, e.get_resolution()) {
+                panic_with_error!(e, Error::RetentionPeriodTooShort);
+            }
+        }
+        ChangeType::GracePeriod => {
+            if change.new_value == 0 {
+                panic_with_error!(e, Error::InvalidGracePeriod);
+            }
+        }
+        ChangeType::Timelock => {}
+    }
+}
+
+fn update_config_version(e: &Env, version: u32) {
+    //the version should increase with every configuration update
+    if version <= e.get_config_version() {
+        panic_with_error!(e, Error::InvalidConfigVersion);
+    }
+    e.set_config_version(version);
+}
+
+fn is_valid_resolution(resolution: u32) -> bool {
+    //resolution should be a whole number of seconds
+    resolution > 0 && (resolution as u64).is_valid_timestamp(1000)
+}
+
+fn is_valid_retention_period(period: u64, resolution: u32) -> bool {
+    //at least two records should be available
+    period >= resolution as u64 * 2
+}
+
+fn is_valid_decimals(decimals: u32) -> bool {
+    (MIN_DECIMALS..=MAX_DECIMALS).contains(&decimals)
+}
+
+fn validate_weights(e: &Env, assets: &Vec<Asset>, weights: &Vec<u32>) {
+    if assets.is_empty()
+        || assets.len() != weights.len()
+        || weights.iter().map(|weight| weight as u64).sum::<u64>() != BASIS_POINTS as u64
+    {
+        panic_with_error!(e, Error::InvalidWeights);
+    }
+}
+
+fn get_basket_price(
+    e: &Env,
+    assets: Vec<Asset>,
+    weights: Vec<u32>,
+    timestamp: u64,
+) -> Option<i128> {
+    let mut prices = Vec::new(e);
+    for (asset, weight) in assets.iter().zip(weights.iter()) {
+        let price = get_price_data(e, asset, timestamp)?.price;
+        prices.push_back((price, weight));
+    }
+    weighted_average(prices.iter())
+}
+
 fn get_x_price(
     e: &Env,
     base_asset: Asset,
@@ -511,7 +2483,7 @@ fn get_x_price(
 
 fn get_x_price_by_indexes(
     e: &Env,
-    asset_pair_indexes: (u8, u8),
+    asset_pair_indexes: (AssetId, AssetId),
     timestamp: u64,
     decimals: u32,
 ) -> Option<PriceData> {
@@ -542,7 +2514,11 @@ fn get_x_price_by_indexes(
     ))
 }
 
-fn get_asset_pair_indexes(e: &Env, base_asset: Asset, quote_asset: Asset) -> Option<(u8, u8)> {
+fn get_asset_pair_indexes(
+    e: &Env,
+    base_asset: Asset,
+    quote_asset: Asset,
+) -> Option<(AssetId, AssetId)> {
     let base_asset = e.get_asset_index(&base_asset);
     if base_asset.is_none() {
         return None;
@@ -556,15 +2532,23 @@ fn get_asset_pair_indexes(e: &Env, base_asset: Asset, quote_asset: Asset) -> Opt
     Some((base_asset.unwrap(), quote_asset.unwrap()))
 }
 
+fn get_asset_index_or_panic(e: &Env, asset: &Asset) -> AssetId {
+    let asset_index = e.get_asset_index(asset);
+    if asset_index.is_none() {
+        panic_with_error!(e, Error::AssetMissing);
+    }
+    asset_index.unwrap()
+}
+
 fn get_price_data(e: &Env, asset: Asset, timestamp: u64) -> Option<PriceData> {
-    let asset: Option<u8> = e.get_asset_index(&asset);
+    let asset: Option<AssetId> = e.get_asset_index(&asset);
     if asset.is_none() {
         return None;
     }
     get_price_data_by_index(e, asset.unwrap(), timestamp)
 }
 
-fn get_price_data_by_index(e: &Env, asset: u8, timestamp: u64) -> Option<PriceData> {
+fn get_price_data_by_index(e: &Env, asset: AssetId, timestamp: u64) -> Option<PriceData> {
     let price = e.get_price(asset, timestamp);
     if price.is_none() {
         return None;
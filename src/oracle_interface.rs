@@ -0,0 +1,44 @@
+use soroban_sdk::{Env, Vec};
+
+use crate::types::{asset::Asset, price_data::PriceData};
+
+// The external price feed API every oracle contract implementation should expose.
+// Implementations delegate to the exported contract functions, so any signature change is caught at compile time.
+#[allow(dead_code)]
+pub trait OracleInterface {
+    fn base(e: &Env) -> Asset;
+
+    fn decimals(e: &Env) -> u32;
+
+    fn resolution(e: &Env) -> u32;
+
+    fn period(e: &Env) -> Option<u64>;
+
+    fn assets(e: &Env) -> Vec<Asset>;
+
+    fn last_timestamp(e: &Env) -> u64;
+
+    fn price(e: &Env, asset: Asset, timestamp: u64) -> Option<PriceData>;
+
+    fn lastprice(e: &Env, asset: Asset) -> Option<PriceData>;
+
+    fn prices(e: &Env, asset: Asset, records: u32) -> Option<Vec<PriceData>>;
+
+    fn x_last_price(e: &Env, base_asset: Asset, quote_asset: Asset) -> Option<PriceData>;
+
+    fn x_price(e: &Env, base_asset: Asset, quote_asset: Asset, timestamp: u64)
+        -> Option<PriceData>;
+
+    fn x_prices(
+        e: &Env,
+        base_asset: Asset,
+        quote_asset: Asset,
+        records: u32,
+    ) -> Option<Vec<PriceData>>;
+
+    fn twap(e: &Env, asset: Asset, records: u32) -> Option<i128>;
+
+    fn x_twap(e: &Env, base_asset: Asset, quote_asset: Asset, records: u32) -> Option<i128>;
+
+    fn version(e: &Env) -> u32;
+}
@@ -4,13 +4,21 @@ extern crate std;
 
 use super::*;
 use alloc::string::ToString;
+use ed25519_dalek::{Signer, SigningKey};
 use soroban_sdk::{
-    testutils::{Address as _, Ledger, LedgerInfo, MockAuth, MockAuthInvoke},
-    Address, Env, String, Symbol, TryIntoVal,
+    symbol_short,
+    testutils::{Address as _, Events, Ledger, LedgerInfo, MockAuth, MockAuthInvoke},
+    Address, Bytes, BytesN, Env, IntoVal, String, Symbol, TryIntoVal, I256,
 };
 use std::panic::{self, AssertUnwindSafe};
 
-use {extensions::i128_extensions::I128Extensions, types::asset::Asset};
+use {
+    extensions::{
+        i128_extensions::{weighted_average, I128Extensions},
+        u128_helper::U128Helper,
+    },
+    types::asset::Asset,
+};
 
 const RESOLUTION: u32 = 300_000;
 const DECIMALS: u32 = 14;
@@ -20,6 +28,13 @@ fn convert_to_seconds(timestamp: u64) -> u64 {
 }
 
 fn init_contract_with_admin<'a>() -> (Env, PriceOracleContractClient<'a>, ConfigData) {
+    init_contract(RESOLUTION, 10)
+}
+
+fn init_contract<'a>(
+    resolution: u32,
+    assets_count: usize,
+) -> (Env, PriceOracleContractClient<'a>, ConfigData) {
     let env = Env::default();
 
     //set timestamp to 900 seconds
@@ -43,11 +58,12 @@ fn init_contract_with_admin<'a>() -> (Env, PriceOracleContractClient<'a>, Config
 
     let init_data = ConfigData {
         admin: admin.clone(),
-        period: (100 * RESOLUTION).into(),
-        assets: generate_assets(&env, 10, 0),
+        period: (100 * resolution).into(),
+        assets: generate_assets(&env, assets_count, 0),
         base_asset: Asset::Stellar(Address::generate(&env)),
         decimals: 14,
-        resolution: RESOLUTION,
+        resolution,
+        version: 1,
     };
 
     env.mock_all_auths();
@@ -77,6 +93,18 @@ fn generate_assets(e: &Env, count: usize, start_index: u32) -> Vec<Asset> {
     assets
 }
 
+fn execute_config_change(env: &Env, client: &PriceOracleContractClient) {
+    let ledger_info = env.ledger().get();
+    let (_, unlock_timestamp) = client.pending_config_change().unwrap();
+    //move ledger time to the unlock timestamp and back
+    env.ledger().set(LedgerInfo {
+        timestamp: unlock_timestamp,
+        ..ledger_info.clone()
+    });
+    client.execute_config_change();
+    env.ledger().set(ledger_info);
+}
+
 fn get_updates(env: &Env, assets: &Vec<Asset>, price: i128) -> Vec<i128> {
     let mut updates = Vec::new(&env);
     for _ in assets.iter() {
@@ -185,180 +213,297 @@ fn set_price_future_timestamp_test() {
 }
 
 #[test]
-fn last_price_test() {
+fn last_price_with_confidence_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
     let assets = init_data.assets;
 
-    let timestamp = 600_000;
+    env.mock_all_auths();
+
+    //no confidence provided
     let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
 
-    env.mock_all_auths();
+    let result = client.lastprice_with_confidence(&assets.get_unchecked(1));
+    assert_eq!(
+        result,
+        Some(PriceWithConfidence {
+            price: normalize_price(100),
+            confidence: 0,
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    //confidence provided
+    let updates = get_updates(&env, &assets, normalize_price(200));
+    let confidence = get_updates(&env, &assets, normalize_price(2));
+    client.set_price_with_confidence(&updates, &900_000, &confidence);
 
-    let timestamp = 900_000;
-    let updates = get_updates(&env, &&assets, normalize_price(200));
+    let result = client.lastprice_with_confidence(&assets.get_unchecked(1));
+    assert_eq!(
+        result,
+        Some(PriceWithConfidence {
+            price: normalize_price(200),
+            confidence: normalize_price(2),
+            timestamp: convert_to_seconds(900_000)
+        })
+    );
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    //overwrite the price without confidence
+    client.set_price(&updates, &900_000);
 
-    //check last prices
-    let result = client.lastprice(&assets.get_unchecked(1));
-    assert_ne!(result, None);
+    let result = client.lastprice_with_confidence(&assets.get_unchecked(1));
     assert_eq!(
         result,
-        Some(PriceData {
+        Some(PriceWithConfidence {
             price: normalize_price(200),
+            confidence: 0,
             timestamp: convert_to_seconds(900_000)
         })
     );
+
+    //unknown asset
+    let result =
+        client.lastprice_with_confidence(&Asset::Other(Symbol::new(&env, "NonRegisteredAsset")));
+    assert_eq!(result, None);
 }
 
 #[test]
-fn last_timestamp_test() {
+fn last_price_with_staleness_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
     let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
 
-    let mut result = client.last_timestamp();
+    env.ledger().set(LedgerInfo {
+        timestamp: 900,
+        ..env.ledger().get()
+    });
 
-    assert_eq!(result, 0);
+    env.mock_all_auths();
 
-    let timestamp = 600_000;
     let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
 
-    env.mock_all_auths();
-
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    let result = client.lastprice_with_staleness(&asset, &300);
+    assert_eq!(
+        result,
+        Some(AggregatedPriceData {
+            price: normalize_price(100),
+            timestamp: 600,
+            age_seconds: 300,
+            is_fresh: true,
+            max_age_seconds: 300,
+        })
+    );
 
-    result = client.last_timestamp();
+    let result = client.lastprice_with_staleness(&asset, &299).unwrap();
+    assert_eq!(result.age_seconds, 300);
+    assert!(!result.is_fresh);
 
-    assert_eq!(result, convert_to_seconds(600_000));
+    //unknown asset
+    let result = client
+        .lastprice_with_staleness(&Asset::Other(Symbol::new(&env, "NonRegisteredAsset")), &300);
+    assert_eq!(result, None);
 }
 
 #[test]
-fn add_assets_test() {
+fn price_round_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
-    let assets = generate_assets(&env, 10, init_data.assets.len() - 1);
+    let assets = init_data.assets;
 
     env.mock_all_auths();
 
-    client.add_assets(&assets);
+    let submitters = Vec::from_array(
+        &env,
+        [
+            Address::generate(&env),
+            Address::generate(&env),
+            Address::generate(&env),
+        ],
+    );
+    client.set_submitters(&submitters);
+    client.set_min_quorum(&3);
+    assert_eq!(client.submitters(), submitters);
+    assert_eq!(client.min_quorum(), 3);
+
+    let round_id = 1;
+    client.open_round(&submitters.get_unchecked(0), &round_id, &600_000);
+
+    let submitted_prices = [100, 101, 1_000_000];
+    for (i, price) in submitted_prices.iter().enumerate() {
+        let updates = get_updates(&env, &assets, normalize_price(*price));
+        client.submit_prices(&submitters.get_unchecked(i as u32), &round_id, &updates);
+    }
 
-    let result = client.assets();
+    client.close_round(&init_data.admin, &round_id);
 
-    let mut expected_assets = init_data.assets.clone();
-    for asset in assets.iter() {
-        expected_assets.push_back(asset.clone());
+    //the outlier is overruled by the median
+    assert_eq!(
+        client.lastprice(&assets.get_unchecked(1)),
+        Some(PriceData {
+            price: normalize_price(101),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+
+    let round_id = 2;
+    client.open_round(&submitters.get_unchecked(0), &round_id, &900_000);
+    let submitted_prices = [100, 102, 1_000_000];
+    for (i, price) in submitted_prices.iter().enumerate() {
+        let updates = get_updates(&env, &assets, normalize_price(*price));
+        client.submit_prices(&submitters.get_unchecked(i as u32), &round_id, &updates);
     }
 
-    assert_eq!(result, expected_assets);
+    //the submission of the removed submitter is ignored
+    client.set_submitters(&submitters.slice(0..2));
+    client.set_min_quorum(&2);
+    client.close_round(&submitters.get_unchecked(0), &round_id);
+
+    assert_eq!(
+        client.lastprice(&assets.get_unchecked(1)),
+        Some(PriceData {
+            price: normalize_price(101),
+            timestamp: convert_to_seconds(900_000)
+        })
+    );
 }
 
 #[test]
-#[should_panic]
-fn add_assets_duplicate_test() {
-    let (env, client, _) = init_contract_with_admin();
+fn last_update_source_test() {
+    let (env, client, init_data) = init_contract_with_admin();
 
-    let mut assets = Vec::new(&env);
-    let duplicate_asset = Asset::Other(Symbol::new(&env, &("ASSET_DUPLICATE")));
-    assets.push_back(duplicate_asset.clone());
-    assets.push_back(duplicate_asset);
+    let assets = init_data.assets;
 
     env.mock_all_auths();
 
-    client.add_assets(&assets);
+    assert_eq!(client.last_update_source(), None);
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+    assert_eq!(client.last_update_source(), Some(init_data.admin));
+
+    let submitters = Vec::from_array(&env, [Address::generate(&env), Address::generate(&env)]);
+    client.set_submitters(&submitters);
+
+    for (i, submitter) in submitters.iter().enumerate() {
+        let round_id = i as u64;
+        client.open_round(&submitter, &round_id, &(600_000 + round_id * 300_000));
+        client.submit_prices(&submitter, &round_id, &updates);
+        client.close_round(&submitter, &round_id);
+        assert_eq!(client.last_update_source(), Some(submitter));
+    }
 }
 
 #[test]
 #[should_panic]
-fn assets_update_overflow_test() {
-    let (env, client, _) = init_contract_with_admin();
+fn price_round_quorum_not_reached_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
 
     env.mock_all_auths();
 
-    env.budget().reset_unlimited();
+    let submitter = Address::generate(&env);
+    client.set_submitters(&Vec::from_array(&env, [submitter.clone()]));
+    client.set_min_quorum(&2);
 
-    let mut assets = Vec::new(&env);
-    for i in 1..=256 {
-        assets.push_back(Asset::Other(Symbol::new(
-            &env,
-            &("Asset".to_string() + &i.to_string()),
-        )));
-    }
+    client.open_round(&submitter, &1, &600_000);
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.submit_prices(&submitter, &1, &updates);
 
-    client.add_assets(&assets);
+    client.close_round(&submitter, &1);
 }
 
 #[test]
 #[should_panic]
-fn prices_update_overflow_test() {
-    let (env, client, _) = init_contract_with_admin();
+fn price_round_removed_submitter_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
 
     env.mock_all_auths();
 
-    env.budget().reset_unlimited();
+    let submitters = Vec::from_array(&env, [Address::generate(&env), Address::generate(&env)]);
+    client.set_submitters(&submitters);
+    client.set_min_quorum(&2);
 
-    let mut updates = Vec::new(&env);
-    for i in 1..=256 {
-        updates.push_back(normalize_price(i as i128 + 1));
+    client.open_round(&submitters.get_unchecked(0), &1, &600_000);
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    for submitter in submitters.iter() {
+        client.submit_prices(&submitter, &1, &updates);
     }
-    client.set_price(&updates, &600_000);
+
+    //the removed submitter's submission no longer counts toward the quorum
+    client.set_submitters(&Vec::from_array(&env, [submitters.get_unchecked(0)]));
+
+    client.close_round(&init_data.admin, &1);
 }
 
 #[test]
-fn set_period_test() {
-    let (env, client, _) = init_contract_with_admin();
+#[should_panic]
+fn price_round_close_unauthorized_test() {
+    let (env, client, init_data) = init_contract_with_admin();
 
-    let period = 100_000;
+    let assets = init_data.assets;
 
     env.mock_all_auths();
 
-    client.set_period(&period);
+    let submitter = Address::generate(&env);
+    client.set_submitters(&Vec::from_array(&env, [submitter.clone()]));
+    client.set_min_quorum(&1);
 
-    let result = client.period().unwrap();
+    client.open_round(&submitter, &1, &600_000);
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.submit_prices(&submitter, &1, &updates);
 
-    assert_eq!(result, convert_to_seconds(period));
+    client.close_round(&Address::generate(&env), &1);
+}
+
+fn sign_price_snapshot(
+    env: &Env,
+    contract: &Address,
+    signing_key: &SigningKey,
+    updates: &Vec<i128>,
+    timestamp: u64,
+) -> BytesN<64> {
+    let mut data = Bytes::from_slice(env, b"reflector:set_price_signed");
+    data.append(&contract.clone().to_xdr(env));
+    for price in updates.iter() {
+        data.extend_from_array(&price.to_be_bytes());
+    }
+    data.extend_from_array(&timestamp.to_be_bytes());
+    let hash = env.crypto().sha256(&data);
+    BytesN::from_array(env, &signing_key.sign(&hash.to_array()).to_bytes())
 }
 
 #[test]
-fn get_price_test() {
+fn set_price_signed_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
     let assets = init_data.assets;
 
-    let timestamp = 600_000;
-    let updates = get_updates(&env, &assets, normalize_price(100));
-
     env.mock_all_auths();
 
-    client.set_price(&updates, &timestamp);
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let signer = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    client.set_signers(&Vec::from_array(&env, [signer.clone()]));
+    assert_eq!(client.signers(), Vec::from_array(&env, [signer.clone()]));
 
-    let timestamp = 900_000;
-    let updates = get_updates(&env, &assets, normalize_price(200));
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    let signature = sign_price_snapshot(&env, &client.address, &signing_key, &updates, 600_000);
 
-    client.set_price(&updates, &timestamp);
+    client.set_price_signed(&updates, &600_000, &signer, &signature);
 
-    //check last prices
-    let mut result = client.lastprice(&assets.get_unchecked(1));
-    assert_ne!(result, None);
-    assert_eq!(
-        result,
-        Some(PriceData {
-            price: normalize_price(200),
-            timestamp: convert_to_seconds(900_000)
-        })
-    );
+    //the message is consumed
+    env.as_contract(&client.address, || {
+        let message = get_signed_snapshot_message(&env, &updates, 600_000);
+        assert!(env.is_signed_update_consumed(&message));
+    });
 
-    //check price at 899_000
-    result = client.price(&assets.get_unchecked(1), &convert_to_seconds(899_000));
-    assert_ne!(result, None);
     assert_eq!(
-        result,
+        client.lastprice(&assets.get_unchecked(1)),
         Some(PriceData {
             price: normalize_price(100),
             timestamp: convert_to_seconds(600_000)
@@ -367,315 +512,3158 @@ fn get_price_test() {
 }
 
 #[test]
-fn get_lastprice_delayed_update_test() {
+#[should_panic]
+fn set_price_signed_invalid_signature_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
     let assets = init_data.assets;
 
-    let timestamp = 300_000;
-    let updates = get_updates(&env, &assets, normalize_price(100));
-
     env.mock_all_auths();
 
-    client.set_price(&updates, &timestamp);
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let signer = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    client.set_signers(&Vec::from_array(&env, [signer.clone()]));
 
-    //check last prices
-    let result = client.lastprice(&assets.get_unchecked(1));
-    assert_eq!(result, None);
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    //signed for another timestamp
+    let signature = sign_price_snapshot(&env, &client.address, &signing_key, &updates, 300_000);
+
+    client.set_price_signed(&updates, &600_000, &signer, &signature);
 }
 
 #[test]
-fn get_x_last_price_test() {
+#[should_panic]
+fn set_price_signed_replay_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
     let assets = init_data.assets;
 
-    let timestamp = 600_000;
-    let updates = get_updates(&env, &assets, normalize_price(100));
-
     env.mock_all_auths();
 
-    client.set_price(&updates, &timestamp);
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let signer = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    client.set_signers(&Vec::from_array(&env, [signer.clone()]));
 
-    //check last x price
-    let result = client.x_last_price(&assets.get_unchecked(1), &assets.get_unchecked(2));
-    assert_ne!(result, None);
-    assert_eq!(
-        result,
-        Some(PriceData {
-            price: normalize_price(1),
-            timestamp: convert_to_seconds(600_000)
-        })
-    );
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    let signature = sign_price_snapshot(&env, &client.address, &signing_key, &updates, 600_000);
+    client.set_price_signed(&updates, &600_000, &signer, &signature);
+
+    //correct the price and try to revert it with the old signed message
+    client.set_price(&get_updates(&env, &assets, normalize_price(101)), &600_000);
+    client.set_price_signed(&updates, &600_000, &signer, &signature);
 }
 
 #[test]
-fn get_x_price_with_zero_test() {
+#[should_panic]
+fn set_price_signed_other_contract_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
     let assets = init_data.assets;
 
-    let timestamp = 600_000;
-    let mut updates = get_updates(&env, &assets, normalize_price(100));
-    updates.set(1, 0);
-
     env.mock_all_auths();
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let signer = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    client.set_signers(&Vec::from_array(&env, [signer.clone()]));
 
-    let result = client.x_price(
-        &assets.get(0).unwrap(),
-        &assets.get(1).unwrap(),
-        &convert_to_seconds(timestamp),
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    //signed for another oracle instance
+    let signature = sign_price_snapshot(
+        &env,
+        &Address::generate(&env),
+        &signing_key,
+        &updates,
+        600_000,
     );
-
-    assert_eq!(result, None);
+    client.set_price_signed(&updates, &600_000, &signer, &signature);
 }
 
 #[test]
-fn get_x_price_test() {
+fn price_history_hash_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
     let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    assert_eq!(client.price_history_hash(&asset, &5), None);
 
-    let timestamp = 600_000;
     let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
 
-    env.mock_all_auths();
+    let hash = client.price_history_hash(&asset, &5).unwrap();
+    let mut data = std::vec::Vec::new();
+    data.extend_from_slice(&convert_to_seconds(600_000).to_le_bytes());
+    data.extend_from_slice(&normalize_price(100).to_le_bytes());
+    assert_eq!(hash, env.crypto().sha256(&Bytes::from_slice(&env, &data)));
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    //stable when no new prices arrive
+    assert_eq!(client.price_history_hash(&asset, &5), Some(hash.clone()));
 
-    let timestamp = 900_000;
+    //changes when a new price is added
     let updates = get_updates(&env, &assets, normalize_price(200));
+    client.set_price(&updates, &900_000);
+    assert_ne!(client.price_history_hash(&asset, &5), Some(hash));
+}
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+#[test]
+fn admin_transfer_test() {
+    let (env, client, init_data) = init_contract_with_admin();
 
-    //check last prices
-    let mut result = client.x_last_price(&assets.get_unchecked(1), &assets.get_unchecked(2));
-    assert_ne!(result, None);
-    assert_eq!(
-        result,
-        Some(PriceData {
-            price: normalize_price(1),
-            timestamp: convert_to_seconds(900_000)
-        })
-    );
+    env.mock_all_auths();
 
-    //check price at 899_000
-    result = client.x_price(
-        &assets.get_unchecked(1),
-        &assets.get_unchecked(2),
-        &convert_to_seconds(899_000),
-    );
-    assert_ne!(result, None);
+    assert_eq!(client.admin_history(), Vec::new(&env));
+
+    let new_admin = Address::generate(&env);
+    client.propose_admin(&new_admin);
+    //the admin is not changed until the proposal is accepted
+    assert_eq!(client.admin(), Some(init_data.admin.clone()));
+
+    client.accept_admin();
+
+    assert_eq!(client.admin(), Some(new_admin));
     assert_eq!(
-        result,
-        Some(PriceData {
-            price: normalize_price(1),
-            timestamp: convert_to_seconds(600_000)
-        })
+        client.admin_history(),
+        Vec::from_array(&env, [init_data.admin])
     );
 }
 
 #[test]
-fn twap_test() {
+fn admin_history_cap_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
-    let assets = init_data.assets;
-
-    let timestamp = 600_000;
-    let updates = get_updates(&env, &assets, normalize_price(100));
-
     env.mock_all_auths();
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    let mut admins = Vec::from_array(&env, [init_data.admin]);
+    for _ in 0..12 {
+        let new_admin = Address::generate(&env);
+        client.propose_admin(&new_admin);
+        client.accept_admin();
+        admins.push_back(new_admin);
+    }
+
+    //the oldest entries are dropped, the current admin is not included
+    let history = client.admin_history();
+    assert_eq!(history.len(), 10);
+    assert_eq!(history, admins.slice(2..12));
+}
+
+#[test]
+fn sep40_compliance_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets.clone();
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
+
+    let expected_price = Some(PriceData {
+        price: normalize_price(100),
+        timestamp: convert_to_seconds(600_000),
+    });
+    assert_eq!(client.base(), init_data.base_asset);
+    assert_eq!(client.assets(), assets);
+    assert_eq!(client.decimals(), init_data.decimals);
+    assert_eq!(client.resolution(), init_data.resolution / 1000);
+    assert_eq!(
+        client.price(&asset, &convert_to_seconds(600_000)),
+        expected_price
+    );
+    assert_eq!(
+        client.prices(&asset, &1),
+        Some(Vec::from_array(&env, [expected_price.clone().unwrap()]))
+    );
+    assert_eq!(client.lastprice(&asset), expected_price);
+    assert_eq!(client.sources(), Vec::from_array(&env, [init_data.admin]));
+}
+
+#[test]
+fn set_price_pyth_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let updates = Vec::from_array(
+        &env,
+        [
+            //123.45678901 with exponent -8
+            PythPriceUpdate {
+                asset: assets.get_unchecked(1),
+                price: 12_345_678_901,
+                confidence: 1_500_000,
+                exponent: -8,
+                publish_time: 600,
+            },
+            PythPriceUpdate {
+                asset: Asset::Other(Symbol::new(&env, "NonRegisteredAsset")),
+                price: 100,
+                confidence: 0,
+                exponent: 0,
+                publish_time: 600,
+            },
+        ],
+    );
+    client.set_price_pyth(&updates, &600_000);
+
+    //converted to 14 decimals
+    assert_eq!(
+        client.lastprice_with_confidence(&assets.get_unchecked(1)),
+        Some(PriceWithConfidence {
+            price: 12_345_678_901_000_000,
+            confidence: 1_500_000_000_000,
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+    assert_eq!(client.lastprice(&assets.get_unchecked(0)), None);
+
+    //exponent below the contract decimals drops extra digits
+    let updates = Vec::from_array(
+        &env,
+        [PythPriceUpdate {
+            asset: assets.get_unchecked(1),
+            price: 123_456,
+            confidence: 0,
+            exponent: -16,
+            publish_time: 900,
+        }],
+    );
+    client.set_price_pyth(&updates, &900_000);
+    assert_eq!(
+        client.lastprice(&assets.get_unchecked(1)).unwrap().price,
+        1_234
+    );
+}
+
+#[test]
+fn set_price_pyth_stale_update_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let update = |asset: u32, publish_time: u64| PythPriceUpdate {
+        asset: assets.get_unchecked(asset),
+        price: 100,
+        confidence: 0,
+        exponent: 0,
+        publish_time,
+    };
+    let updates = Vec::from_array(
+        &env,
+        [
+            //published exactly one resolution before the snapshot
+            update(1, 600),
+            //published more than one resolution before the snapshot
+            update(2, 599),
+        ],
+    );
+    client.set_price_pyth(&updates, &900_000);
+
+    assert_eq!(
+        client.lastprice(&assets.get_unchecked(1)).unwrap().price,
+        normalize_price(100)
+    );
+    assert_eq!(client.lastprice(&assets.get_unchecked(2)), None);
+}
+
+#[test]
+fn single_record_twap_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.ledger().set(LedgerInfo {
+        timestamp: 900,
+        ..env.ledger().get()
+    });
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(123));
+    client.set_price(&updates, &600_000);
+
+    //twap over a single record equals the record price
+    let twap = client.twap(&asset, &1);
+    let last_price = client.lastprice(&asset).unwrap();
+    assert_eq!(twap, Some(last_price.price));
+
+    //all requested records are required
+    assert_eq!(client.twap(&asset, &2), None);
+}
+
+#[test]
+fn x_price_symmetry_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset_a = assets.get_unchecked(1);
+    let asset_b = assets.get_unchecked(2);
+
+    env.mock_all_auths();
+
+    //equal prices
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+
+    let timestamp = convert_to_seconds(300_000);
+    let direct = client
+        .x_price(&asset_a, &asset_b, &timestamp)
+        .unwrap()
+        .price;
+    let inverse = client
+        .x_price(&asset_b, &asset_a, &timestamp)
+        .unwrap()
+        .price;
+    assert_eq!(direct.fixed_mul_floor(inverse, 0), 10i128.pow(DECIMALS * 2));
+
+    //unequal prices
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.set(2, normalize_price(300));
+    client.set_price(&updates, &600_000);
+
+    let timestamp = convert_to_seconds(600_000);
+    let direct = client
+        .x_price(&asset_a, &asset_b, &timestamp)
+        .unwrap()
+        .price;
+    let inverse = client
+        .x_price(&asset_b, &asset_a, &timestamp)
+        .unwrap()
+        .price;
+    let product = direct.fixed_mul_floor(inverse, DECIMALS);
+    assert!((product - 10i128.pow(DECIMALS)).abs() <= 2);
+}
+
+#[test]
+fn same_timestamp_update_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    //last write wins
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
+    let updates = get_updates(&env, &assets, normalize_price(200));
+    client.set_price(&updates, &600_000);
+
+    assert_eq!(
+        client.price(&asset, &convert_to_seconds(600_000)),
+        Some(PriceData {
+            price: normalize_price(200),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+}
+
+#[test]
+fn different_timestamps_update_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
+    let updates = get_updates(&env, &assets, normalize_price(200));
+    client.set_price(&updates, &900_000);
+
+    assert_eq!(
+        client.price(&asset, &convert_to_seconds(600_000)),
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+    assert_eq!(
+        client.price(&asset, &convert_to_seconds(900_000)),
+        Some(PriceData {
+            price: normalize_price(200),
+            timestamp: convert_to_seconds(900_000)
+        })
+    );
+}
+
+#[test]
+fn set_price_deduplicated_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price_deduplicated(&updates, &600_000);
+
+    //different batch at the same timestamp
+    let updates = get_updates(&env, &assets, normalize_price(200));
+    client.set_price_deduplicated(&updates, &600_000);
+
+    assert_eq!(
+        client.lastprice(&assets.get_unchecked(1)),
+        Some(PriceData {
+            price: normalize_price(200),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+}
+
+#[test]
+#[should_panic]
+fn set_price_deduplicated_replay_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price_deduplicated(&updates, &600_000);
+    client.set_price_deduplicated(&updates, &600_000);
+}
+
+#[test]
+fn last_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let timestamp = 600_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let timestamp = 900_000;
+    let updates = get_updates(&env, &&assets, normalize_price(200));
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    //check last prices
+    let result = client.lastprice(&assets.get_unchecked(1));
+    assert_ne!(result, None);
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(200),
+            timestamp: convert_to_seconds(900_000)
+        })
+    );
+}
+
+#[test]
+fn raw_resolution_and_period_test() {
+    let (_, client, init_data) = init_contract_with_admin();
+
+    assert_eq!(client.resolution_ms(), init_data.resolution);
+    assert_eq!(client.resolution(), client.resolution_ms() / 1000);
+    assert_eq!(client.period_ms(), Some(init_data.period));
+    assert_eq!(client.period(), Some(init_data.period / 1000));
+}
+
+#[test]
+fn retention_period_expiration_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    //keep 3 records
+    client.set_period(&(RESOLUTION as u64 * 3), &(init_data.version + 1));
+    execute_config_change(&env, &client);
+
+    //each record lives for period / 5 + 1 = 181 ledgers after its submission
+    for (i, timestamp) in [300_000, 600_000, 900_000].iter().enumerate() {
+        //ledgers are closed every 5 seconds
+        env.ledger().set(LedgerInfo {
+            timestamp: convert_to_seconds(*timestamp),
+            sequence_number: (convert_to_seconds(*timestamp) / 5) as u32,
+            ..env.ledger().get()
+        });
+        let updates = get_updates(&env, &assets, normalize_price(100 + i as i128));
+        client.set_price(&updates, timestamp);
+    }
+    assert_eq!(client.prices(&asset, &5).unwrap().len(), 3);
+
+    //the first record is out of the retention window
+    env.ledger().set(LedgerInfo {
+        timestamp: 1_300,
+        sequence_number: 260,
+        ..env.ledger().get()
+    });
+
+    assert_eq!(
+        client.lastprice(&asset),
+        Some(PriceData {
+            price: normalize_price(102),
+            timestamp: convert_to_seconds(900_000)
+        })
+    );
+    assert_eq!(
+        client.last_n_timestamps(&5),
+        Vec::from_array(&env, [900, 600])
+    );
+
+    //all records are out of the retention window
+    env.ledger().set(LedgerInfo {
+        timestamp: 1_900,
+        sequence_number: 380,
+        ..env.ledger().get()
+    });
+
+    assert_eq!(client.last_n_timestamps(&5), Vec::new(&env));
+}
+
+#[test]
+fn last_timestamp_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let mut result = client.last_timestamp();
+
+    assert_eq!(result, 0);
+
+    let timestamp = 600_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    result = client.last_timestamp();
+
+    assert_eq!(result, convert_to_seconds(600_000));
+    //same unit as the price records timestamp
+    assert_eq!(
+        result,
+        client
+            .lastprice(&assets.get_unchecked(1))
+            .unwrap()
+            .timestamp
+    );
+}
+
+#[test]
+fn add_assets_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = generate_assets(&env, 10, init_data.assets.len() - 1);
+
+    env.mock_all_auths();
+
+    client.add_assets(&assets, &(init_data.version + 1));
+
+    let result = client.assets();
+
+    let mut expected_assets = init_data.assets.clone();
+    for asset in assets.iter() {
+        expected_assets.push_back(asset.clone());
+    }
+
+    assert_eq!(result, expected_assets);
+}
+
+#[test]
+#[should_panic]
+fn add_assets_duplicate_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let mut assets = Vec::new(&env);
+    let duplicate_asset = Asset::Other(Symbol::new(&env, &("ASSET_DUPLICATE")));
+    assets.push_back(duplicate_asset.clone());
+    assets.push_back(duplicate_asset);
+
+    env.mock_all_auths();
+
+    client.add_assets(&assets, &(init_data.version + 1));
+}
+
+#[test]
+#[should_panic]
+fn assets_update_overflow_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    env.budget().reset_unlimited();
+
+    let mut assets = Vec::new(&env);
+    for i in 1..=256 {
+        assets.push_back(Asset::Other(Symbol::new(
+            &env,
+            &("Asset".to_string() + &i.to_string()),
+        )));
+    }
+
+    client.add_assets(&assets, &(init_data.version + 1));
+}
+
+#[test]
+#[should_panic]
+fn prices_update_overflow_test() {
+    let (env, client, _) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    env.budget().reset_unlimited();
+
+    let mut updates = Vec::new(&env);
+    for i in 1..=256 {
+        updates.push_back(normalize_price(i as i128 + 1));
+    }
+    client.set_price(&updates, &600_000);
+}
+
+#[test]
+fn set_period_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let period = 1_000_000;
+
+    env.mock_all_auths();
+
+    client.set_period(&period, &(init_data.version + 1));
+
+    //the period is applied after the timelock
+    assert_eq!(client.period(), Some(convert_to_seconds(init_data.period)));
+    execute_config_change(&env, &client);
+
+    let result = client.period().unwrap();
+
+    assert_eq!(result, convert_to_seconds(period));
+}
+
+#[test]
+fn config_change_timelock_test() {
+    let (env, client, _) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    let period = 1_000_000;
+    let change = ConfigChange {
+        change_type: ChangeType::Period,
+        new_value: period,
+    };
+    client.propose_config_change(&change);
+
+    let unlock_timestamp = env.ledger().timestamp() + client.config_timelock();
+    assert_eq!(
+        client.pending_config_change(),
+        Some((change, unlock_timestamp))
+    );
+
+    //move ledger time to the unlock timestamp
+    let ledger_info = env.ledger().get();
+    env.ledger().set(LedgerInfo {
+        timestamp: unlock_timestamp,
+        ..ledger_info
+    });
+
+    let config_version = client.config_version();
+    client.execute_config_change();
+
+    assert_eq!(client.period_ms(), Some(period));
+    assert_eq!(client.config_version(), config_version + 1);
+    assert_eq!(client.pending_config_change(), None);
+}
+
+#[test]
+fn config_change_grace_period_and_timelock_test() {
+    let (env, client, _) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.propose_config_change(&ConfigChange {
+        change_type: ChangeType::Timelock,
+        new_value: 60,
+    });
+    let (_, unlock_timestamp) = client.pending_config_change().unwrap();
+    let ledger_info = env.ledger().get();
+    env.ledger().set(LedgerInfo {
+        timestamp: unlock_timestamp,
+        ..ledger_info
+    });
+    client.execute_config_change();
+    assert_eq!(client.config_timelock(), 60);
+
+    //the new timelock applies to the next proposals
+    client.propose_config_change(&ConfigChange {
+        change_type: ChangeType::GracePeriod,
+        new_value: 120,
+    });
+    let (_, unlock_timestamp) = client.pending_config_change().unwrap();
+    assert_eq!(unlock_timestamp, env.ledger().timestamp() + 60);
+
+    let ledger_info = env.ledger().get();
+    env.ledger().set(LedgerInfo {
+        timestamp: unlock_timestamp,
+        ..ledger_info
+    });
+    client.execute_config_change();
+    assert_eq!(client.grace_period(), 120);
+}
+
+#[test]
+fn cancel_config_change_test() {
+    let (env, client, _) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.propose_config_change(&ConfigChange {
+        change_type: ChangeType::Period,
+        new_value: 1_000_000,
+    });
+    client.cancel_config_change();
+
+    assert_eq!(client.pending_config_change(), None);
+}
+
+#[test]
+#[should_panic]
+fn execute_locked_config_change_test() {
+    let (env, client, _) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.propose_config_change(&ConfigChange {
+        change_type: ChangeType::Period,
+        new_value: 1_000_000,
+    });
+    client.execute_config_change();
+}
+
+#[test]
+#[should_panic]
+fn propose_invalid_config_change_test() {
+    let (env, client, _) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.propose_config_change(&ConfigChange {
+        change_type: ChangeType::GracePeriod,
+        new_value: 0,
+    });
+}
+
+#[test]
+fn oracle_uptime_stats_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let ledger_info = env.ledger().get();
+    env.ledger().set(LedgerInfo {
+        timestamp: 3_000,
+        ..ledger_info
+    });
+
+    env.mock_all_auths();
+
+    assert_eq!(client.oracle_uptime_stats(), UptimeStats::default());
+
+    let updates = get_updates(&env, &init_data.assets, normalize_price(100));
+
+    //regular updates
+    for timestamp in [300_000, 600_000, 900_000] {
+        client.set_price(&updates, &timestamp);
+    }
+    assert_eq!(
+        client.oracle_uptime_stats(),
+        UptimeStats {
+            total_updates: 3,
+            last_update_timestamp: 900,
+            consecutive_missed_intervals: 0
+        }
+    );
+
+    //skip 3 intervals
+    client.set_price(&updates, &2_100_000);
+    assert_eq!(
+        client.oracle_uptime_stats(),
+        UptimeStats {
+            total_updates: 4,
+            last_update_timestamp: 2_100,
+            consecutive_missed_intervals: 3
+        }
+    );
+
+    //the next regular update resets the missed intervals
+    client.set_price(&updates, &2_400_000);
+    assert_eq!(
+        client.oracle_uptime_stats(),
+        UptimeStats {
+            total_updates: 5,
+            last_update_timestamp: 2_400,
+            consecutive_missed_intervals: 0
+        }
+    );
+}
+
+#[test]
+fn get_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let timestamp = 600_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    client.set_price(&updates, &timestamp);
+
+    let timestamp = 900_000;
+    let updates = get_updates(&env, &assets, normalize_price(200));
+
+    client.set_price(&updates, &timestamp);
+
+    //check last prices
+    let mut result = client.lastprice(&assets.get_unchecked(1));
+    assert_ne!(result, None);
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(200),
+            timestamp: convert_to_seconds(900_000)
+        })
+    );
+
+    //check price at 899_000
+    result = client.price(&assets.get_unchecked(1), &convert_to_seconds(899_000));
+    assert_ne!(result, None);
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+}
+
+#[test]
+fn get_lastprice_delayed_update_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let timestamp = 300_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    client.set_price(&updates, &timestamp);
+
+    //check last prices
+    let result = client.lastprice(&assets.get_unchecked(1));
+    assert_eq!(result, None);
+}
+
+#[test]
+fn get_x_last_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let timestamp = 600_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    client.set_price(&updates, &timestamp);
+
+    //check last x price
+    let result = client.x_last_price(&assets.get_unchecked(1), &assets.get_unchecked(2));
+    assert_ne!(result, None);
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(1),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+}
+
+#[test]
+fn get_x_price_with_zero_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let timestamp = 600_000;
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.set(1, 0);
+
+    env.mock_all_auths();
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let result = client.x_price(
+        &assets.get(0).unwrap(),
+        &assets.get(1).unwrap(),
+        &convert_to_seconds(timestamp),
+    );
+
+    assert_eq!(result, None);
+}
+
+#[test]
+fn get_x_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let timestamp = 600_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let timestamp = 900_000;
+    let updates = get_updates(&env, &assets, normalize_price(200));
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    //check last prices
+    let mut result = client.x_last_price(&assets.get_unchecked(1), &assets.get_unchecked(2));
+    assert_ne!(result, None);
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(1),
+            timestamp: convert_to_seconds(900_000)
+        })
+    );
+
+    //check price at 899_000
+    result = client.x_price(
+        &assets.get_unchecked(1),
+        &assets.get_unchecked(2),
+        &convert_to_seconds(899_000),
+    );
+    assert_ne!(result, None);
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(1),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+}
+
+#[test]
+fn twap_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let timestamp = 600_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let timestamp = 900_000;
+    let updates = get_updates(&env, &assets, normalize_price(200));
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let result = client.twap(&assets.get_unchecked(1), &2);
+
+    assert_ne!(result, None);
+    assert_eq!(result.unwrap(), normalize_price(150));
+}
+
+#[test]
+fn x_twap_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    //set prices for assets
+    let timestamp = 600_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let timestamp = 900_000;
+    let updates = get_updates(&env, &assets, normalize_price(200));
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let result = client.x_twap(&assets.get_unchecked(1), &assets.get_unchecked(2), &2);
+
+    assert_ne!(result, None);
+    assert_eq!(result.unwrap(), normalize_price(1));
+}
+
+#[test]
+#[should_panic]
+fn x_twap_with_gap_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    //set prices for assets with gap
+    let timestamp = 300_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+
+    env.mock_all_auths();
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let timestamp = 900_000;
+    let updates = get_updates(&env, &assets, normalize_price(200));
+
+    //set prices for assets
+    client.set_price(&updates, &timestamp);
+
+    let result = client.x_twap(&assets.get_unchecked(1), &assets.get_unchecked(2), &3);
+
+    assert_ne!(result, None);
+    assert_eq!(result.unwrap(), normalize_price(1));
+}
+
+#[test]
+fn get_non_registered_asset_price_test() {
+    let (env, client, config_data) = init_contract_with_admin();
+
+    //try to get price for unknown Stellar asset
+    let mut result = client.lastprice(&Asset::Stellar(Address::generate(&env)));
+    assert_eq!(result, None);
+
+    //try to get price for unknown Other asset
+    result = client.lastprice(&Asset::Other(Symbol::new(&env, "NonRegisteredAsset")));
+    assert_eq!(result, None);
+
+    //try to get price for unknown base asset
+    result = client.x_last_price(
+        &Asset::Stellar(Address::generate(&env)),
+        &config_data.assets.get_unchecked(1),
+    );
+    assert_eq!(result, None);
+
+    //try to get price for unknown quote asset
+    result = client.x_last_price(
+        &config_data.assets.get_unchecked(1),
+        &Asset::Stellar(Address::generate(&env)),
+    );
+    assert_eq!(result, None);
+
+    //try to get price for both unknown assets
+    result = client.x_last_price(
+        &Asset::Stellar(Address::generate(&env)),
+        &Asset::Other(Symbol::new(&env, "NonRegisteredAsset")),
+    );
+    assert_eq!(result, None);
+}
+
+#[test]
+fn get_asset_price_for_invalid_timestamp_test() {
+    let (env, client, config_data) = init_contract_with_admin();
+
+    let mut result = client.price(
+        &config_data.assets.get_unchecked(1),
+        &convert_to_seconds(u64::MAX),
+    );
+    assert_eq!(result, None);
+
+    //try to get price for unknown asset
+    result = client.lastprice(&Asset::Stellar(Address::generate(&env)));
+    assert_eq!(result, None);
+}
+
+#[test]
+fn authorized_test() {
+    let (env, client, config_data) = init_contract_with_admin();
+
+    let period: u64 = 1_000_000;
+    let version: u32 = config_data.version + 1;
+    //set prices for assets
+    client
+        .mock_auths(&[MockAuth {
+            address: &config_data.admin,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "set_period",
+                args: Vec::from_array(
+                    &env,
+                    [
+                        period.clone().try_into_val(&env).unwrap(),
+                        version.try_into_val(&env).unwrap(),
+                    ],
+                ),
+                sub_invokes: &[],
+            },
+        }])
+        .set_period(&period, &version);
+}
+
+#[test]
+#[should_panic]
+fn unauthorized_test() {
+    let (env, client, config_data) = init_contract_with_admin();
+
+    let account = Address::generate(&env);
+
+    let period: u64 = 1_000_000;
+    let version: u32 = config_data.version + 1;
+    //set prices for assets
+    client
+        .mock_auths(&[MockAuth {
+            address: &account,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "set_period",
+                args: Vec::from_array(
+                    &env,
+                    [
+                        period.clone().try_into_val(&env).unwrap(),
+                        version.try_into_val(&env).unwrap(),
+                    ],
+                ),
+                sub_invokes: &[],
+            },
+        }])
+        .set_period(&period, &version);
+}
+
+#[test]
+#[should_panic]
+fn unauthorized_set_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let account = Address::generate(&env);
+    let updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    let timestamp: u64 = 600_000;
+
+    client
+        .mock_auths(&[MockAuth {
+            address: &account,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "set_price",
+                args: (updates.clone(), timestamp).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .set_price(&updates, &timestamp);
+}
+
+#[test]
+#[should_panic]
+fn unauthorized_propose_config_change_test() {
+    let (env, client, _init_data) = init_contract_with_admin();
+
+    let account = Address::generate(&env);
+    let change = ConfigChange {
+        change_type: ChangeType::Timelock,
+        new_value: 0,
+    };
+
+    client
+        .mock_auths(&[MockAuth {
+            address: &account,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "propose_config_change",
+                args: (change.clone(),).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .propose_config_change(&change);
+}
+
+#[test]
+#[should_panic]
+fn not_initialized_admin_test() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, PriceOracleContract);
+    let client = PriceOracleContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    //there is no admin to authorize the call
+    client.set_grace_period(&60);
+}
+
+#[test]
+fn div_tests() {
+    let test_cases = [
+        (154467226919499, 133928752749774, 115335373284703),
+        (
+            i128::MAX / 100,
+            231731687303715884105728,
+            734216306110962248249052545,
+        ),
+        (231731687303715884105728, i128::MAX / 100, 13),
+        // -1 expected result for errors
+        (1, 0, -1),
+        (0, 1, -1),
+        (0, 0, -1),
+        (-1, 0, -1),
+        (0, -1, -1),
+        (-1, -1, -1),
+    ];
+
+    for (a, b, expected) in test_cases.iter() {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| a.fixed_div_floor(*b, 14)));
+        if expected == &-1 {
+            assert!(result.is_err());
+        } else {
+            assert_eq!(result.unwrap(), *expected);
+        }
+    }
+}
+
+#[test]
+fn div_near_overflow_test() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+
+    //exponential steps from 1 to i128::MAX / 10
+    let mut values = std::vec::Vec::new();
+    let mut value = 1i128;
+    while value <= i128::MAX / 10 {
+        values.push(value);
+        value *= 10;
+    }
+    values.push(i128::MAX / 10);
+
+    for a in values.iter() {
+        for b in values.iter() {
+            for decimals in 1..=18 {
+                //exact floor result, None if it doesn't fit into i128
+                let exact = I256::from_i128(&env, *a)
+                    .mul(&I256::from_i128(&env, 10).pow(decimals))
+                    .div(&I256::from_i128(&env, *b))
+                    .to_i128();
+                let result =
+                    panic::catch_unwind(AssertUnwindSafe(|| a.fixed_div_floor(*b, decimals)));
+                assert_eq!(
+                    result.ok(),
+                    exact,
+                    "{} / {} with {} decimals",
+                    a,
+                    b,
+                    decimals
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn asset_metadata_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    assert_eq!(client.get_asset_metadata(&asset), None);
+
+    let metadata = AssetMetadata {
+        name: Symbol::new(&env, "Bitcoin"),
+        description: Symbol::new(&env, "Native_BTC"),
+        decimals_override: Some(8),
+    };
+    client.set_asset_metadata(&asset, &metadata);
+
+    assert_eq!(client.get_asset_metadata(&asset), Some(metadata));
+    //other assets are not affected
+    assert_eq!(client.get_asset_metadata(&assets.get_unchecked(0)), None);
+}
+
+#[test]
+#[should_panic]
+fn set_asset_metadata_invalid_decimals_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.set_asset_metadata(
+        &init_data.assets.get_unchecked(1),
+        &AssetMetadata {
+            name: Symbol::new(&env, "Bitcoin"),
+            description: Symbol::new(&env, "Native_BTC"),
+            decimals_override: Some(MAX_DECIMALS + 1),
+        },
+    );
+}
+
+#[test]
+fn price_bounds_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    assert_eq!(client.get_price_bounds(&asset), None);
+
+    client.set_price_bounds(&asset, &normalize_price(100), &normalize_price(200));
+
+    assert_eq!(
+        client.get_price_bounds(&asset),
+        Some((normalize_price(100), normalize_price(200)))
+    );
+
+    //price exactly at the lower boundary is accepted
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+    assert_eq!(
+        client.price(&asset, &convert_to_seconds(300_000)),
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(300_000)
+        })
+    );
+
+    //price exactly at the upper boundary is accepted
+    updates.set(1, normalize_price(200));
+    client.set_price(&updates, &600_000);
+    assert_eq!(
+        client.price(&asset, &convert_to_seconds(600_000)),
+        Some(PriceData {
+            price: normalize_price(200),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+
+    //price one unit outside the boundary is skipped, other assets are updated
+    updates.set(1, normalize_price(200) + 1);
+    client.set_price(&updates, &900_000);
+    assert_eq!(client.lastprice(&asset), None);
+    assert_ne!(client.lastprice(&assets.get_unchecked(0)), None);
+
+    //bounds removal
+    client.clear_price_bounds(&asset);
+    assert_eq!(client.get_price_bounds(&asset), None);
+}
+
+#[test]
+fn price_below_bounds_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    client.set_price_bounds(&asset, &normalize_price(100), &normalize_price(200));
+
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.set(1, normalize_price(100) - 1);
+    client.set_price(&updates, &600_000);
+
+    assert_eq!(client.lastprice(&asset), None);
+}
+
+#[test]
+#[should_panic]
+fn price_out_of_bounds_strict_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    client.set_strict_price_bounds(&true);
+    client.set_price_bounds(
+        &assets.get_unchecked(1),
+        &normalize_price(100),
+        &normalize_price(200),
+    );
+
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.set(1, normalize_price(200) + 1);
+    client.set_price(&updates, &600_000);
+}
+
+#[test]
+fn validate_price_update_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    client.set_price_bounds(
+        &assets.get_unchecked(1),
+        &normalize_price(100),
+        &normalize_price(200),
+    );
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    assert_eq!(
+        client.validate_price_update(&updates, &600_000),
+        Vec::new(&env)
+    );
+
+    client.set_price_bounds(&assets.get_unchecked(3), &1, &normalize_price(200));
+
+    let mut updates = updates;
+    updates.set(1, normalize_price(200) + 1);
+    updates.set(3, -normalize_price(100));
+    //zero price means no price
+    updates.set(4, 0);
+
+    //out-of-bounds prices are skipped without strict price bounds
+    assert_eq!(
+        client.validate_price_update(&updates, &600_000),
+        Vec::new(&env)
+    );
+
+    client.set_strict_price_bounds(&true);
+
+    //not aligned to the resolution
+    let issues = client.validate_price_update(&updates, &600_001);
+    assert_eq!(
+        issues,
+        Vec::from_array(
+            &env,
+            [
+                (255, Error::InvalidTimestamp),
+                (1, Error::PriceOutOfBounds),
+                (3, Error::PriceOutOfBounds)
+            ]
+        )
+    );
+
+    //nothing is recorded
+    assert_eq!(client.last_timestamp(), 0);
+}
+
+#[test]
+#[should_panic]
+fn invalid_price_bounds_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.set_price_bounds(
+        &init_data.assets.get_unchecked(1),
+        &normalize_price(200),
+        &normalize_price(100),
+    );
+}
+
+#[test]
+fn grace_period_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    //defaults to two resolution steps
+    assert_eq!(
+        client.grace_period(),
+        convert_to_seconds(RESOLUTION as u64 * 2)
+    );
+
+    let timestamp = 300_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &timestamp);
+
+    //the last price is outside the default grace period
+    assert_eq!(client.lastprice(&assets.get_unchecked(1)), None);
+
+    client.set_grace_period(&convert_to_seconds(RESOLUTION as u64 * 3));
+    execute_config_change(&env, &client);
+    assert_eq!(
+        client.grace_period(),
+        convert_to_seconds(RESOLUTION as u64 * 3)
+    );
+
+    let result = client.lastprice(&assets.get_unchecked(1));
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(timestamp)
+        })
+    );
+}
+
+#[test]
+fn runtime_resolution_test() {
+    let (env, client, init_data) = init_contract(60_000, 10);
+
+    let assets = init_data.assets;
+
+    assert_eq!(client.resolution(), 60);
+    assert_eq!(client.period(), Some(convert_to_seconds(100 * 60_000)));
+
+    env.mock_all_auths();
+
+    //the timestamp is valid for one minute resolution, but not for five minutes resolution
+    let timestamp = 840_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &timestamp);
+
+    //price timestamp is normalized using the configured resolution
+    let result = client.price(&assets.get_unchecked(1), &convert_to_seconds(899_000));
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(840_000)
+        })
+    );
+
+    let (env, client, init_data) = init_contract(RESOLUTION, 10);
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let timestamp = 600_000;
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &timestamp);
+
+    let result = client.price(&assets.get_unchecked(1), &convert_to_seconds(899_000));
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+}
+
+#[test]
+fn update_base_asset_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let new_base = Asset::Other(Symbol::new(&env, "USD"));
+
+    env.mock_all_auths();
+
+    client.update_base_asset(&new_base);
+
+    assert_eq!(client.base(), new_base);
+    assert_eq!(client.config_version(), init_data.version + 1);
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, client.address);
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("oracle"),
+            Symbol::new(&env, "base_asset_updated")
+        )
+            .into_val(&env)
+    );
+    let data: (Asset, Asset) = data.into_val(&env);
+    assert_eq!(data, (init_data.base_asset, new_base));
+}
+
+#[test]
+fn twap_range_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+
+    //gap at 600_000
+
+    let updates = get_updates(&env, &assets, normalize_price(200));
+    client.set_price(&updates, &900_000);
+
+    let asset = assets.get_unchecked(1);
+
+    //gaps are ignored
+    let result = client.twap_range(&asset, &0, &convert_to_seconds(900_000));
+    assert_eq!(result, Some(normalize_price(150)));
+
+    //timestamps are normalized
+    let result = client.twap_range(&asset, &301, &convert_to_seconds(899_000));
+    assert_eq!(result, Some(normalize_price(100)));
+
+    //no prices in range
+    let result = client.twap_range(&asset, &600, &600);
+    assert_eq!(result, None);
+
+    //invalid range
+    let result = client.twap_range(&asset, &900, &300);
+    assert_eq!(result, None);
+
+    //range end is clamped to the last timestamp
+    let result = client.twap_range(&asset, &0, &(u64::MAX / 1000));
+    assert_eq!(result, Some(normalize_price(150)));
+
+    //range end overflows when converted to milliseconds
+    let result = client.twap_range(&asset, &0, &u64::MAX);
+    assert_eq!(result, None);
+
+    //only the last 20 resolution steps of the range are walked
+    let timestamp = 900_000 + 19 * 300_000;
+    env.ledger().set(LedgerInfo {
+        timestamp: convert_to_seconds(timestamp),
+        ..env.ledger().get()
+    });
+    let updates = get_updates(&env, &assets, normalize_price(300));
+    client.set_price(&updates, &timestamp);
+    let result = client.twap_range(&asset, &0, &(u64::MAX / 1000));
+    assert_eq!(result, Some(normalize_price(250)));
+
+    let result = client.x_twap_range(
+        &asset,
+        &assets.get_unchecked(2),
+        &0,
+        &convert_to_seconds(900_000),
+    );
+    assert_eq!(result, Some(normalize_price(1)));
+
+    //unknown asset
+    let result = client.twap_range(
+        &Asset::Other(Symbol::new(&env, "NonRegisteredAsset")),
+        &0,
+        &convert_to_seconds(900_000),
+    );
+    assert_eq!(result, None);
+}
+
+#[test]
+fn basket_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.set(2, normalize_price(200));
+    client.set_price(&updates, &600_000);
+
+    //50/50 basket of two equal-priced assets
+    let basket = Vec::from_array(&env, [assets.get_unchecked(0), assets.get_unchecked(1)]);
+    let weights = Vec::from_array(&env, [5_000, 5_000]);
+    let result = client.basket_price(&basket, &weights);
+    assert_eq!(result, Some(normalize_price(100)));
+
+    //75/25 basket of assets with different prices
+    let basket = Vec::from_array(&env, [assets.get_unchecked(1), assets.get_unchecked(2)]);
+    let weights = Vec::from_array(&env, [7_500, 2_500]);
+    let result = client.basket_price(&basket, &weights);
+    assert_eq!(result, Some(normalize_price(125)));
+
+    let result = client.x_basket_price(&basket, &weights, &assets.get_unchecked(2));
+    assert_eq!(result, Some(normalize_price(1) * 5 / 8));
+
+    //unknown asset
+    let basket = Vec::from_array(
+        &env,
+        [
+            assets.get_unchecked(1),
+            Asset::Other(Symbol::new(&env, "NonRegisteredAsset")),
+        ],
+    );
+    let result = client.basket_price(&basket, &weights);
+    assert_eq!(result, None);
+}
+
+#[test]
+#[should_panic]
+fn basket_price_invalid_weights_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    let basket = Vec::from_array(&env, [assets.get_unchecked(0), assets.get_unchecked(1)]);
+    let weights = Vec::from_array(&env, [5_000, 4_999]);
+    client.basket_price(&basket, &weights);
+}
+
+#[test]
+fn mul_tests() {
+    let test_cases = [
+        (normalize_price(100), 5_000, 4, normalize_price(50)),
+        (154467226919499, 133928752749774, 14, 206876030420448),
+        (1, 1, 1, 0),
+    ];
+
+    for (a, b, decimals, expected) in test_cases.iter() {
+        assert_eq!(a.fixed_mul_floor(*b, *decimals), *expected);
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| i128::MAX.fixed_mul_floor(2, 0)));
+    assert!(result.is_err());
+}
+
+#[test]
+fn ohlc_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.ledger().set(LedgerInfo {
+        timestamp: 1_500,
+        ..env.ledger().get()
+    });
+
+    env.mock_all_auths();
+
+    let prices = [
+        (300_000, 150),
+        (600_000, 300),
+        (900_000, 100),
+        (1_500_000, 200),
+    ];
+    for (timestamp, price) in prices.iter() {
+        let updates = get_updates(&env, &assets, normalize_price(*price));
+        client.set_price(&updates, timestamp);
+    }
+
+    let result = client.ohlc(&asset, &0, &convert_to_seconds(1_500_000));
+    assert_eq!(
+        result,
+        Some(OHLCData {
+            open: normalize_price(150),
+            high: normalize_price(300),
+            low: normalize_price(100),
+            close: normalize_price(200),
+            timestamp_open: convert_to_seconds(300_000),
+            timestamp_close: convert_to_seconds(1_500_000),
+        })
+    );
+
+    //no prices in range
+    let result = client.ohlc(&asset, &1_200, &1_200);
+    assert_eq!(result, None);
+
+    //unknown asset
+    let result = client.ohlc(
+        &Asset::Other(Symbol::new(&env, "NonRegisteredAsset")),
+        &0,
+        &convert_to_seconds(1_500_000),
+    );
+    assert_eq!(result, None);
+}
+
+#[test]
+fn ohlcv_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.ledger().set(LedgerInfo {
+        timestamp: 1_500,
+        ..env.ledger().get()
+    });
+
+    env.mock_all_auths();
+
+    //gap at 1_200_000
+    let prices = [
+        (300_000, 150),
+        (600_000, 300),
+        (900_000, 100),
+        (1_500_000, 200),
+    ];
+    for (timestamp, price) in prices.iter() {
+        let updates = get_updates(&env, &assets, normalize_price(*price));
+        client.set_price(&updates, timestamp);
+    }
+
+    //multiple records
+    let result = client.ohlcv(&asset, &0, &convert_to_seconds(1_500_000));
+    assert_eq!(
+        result,
+        Some(OHLCVData {
+            open: normalize_price(150),
+            high: normalize_price(300),
+            low: normalize_price(100),
+            close: normalize_price(200),
+            timestamp_open: convert_to_seconds(300_000),
+            timestamp_close: convert_to_seconds(1_500_000),
+            record_count: 4,
+        })
+    );
+
+    //single record
+    let result = client.ohlcv(
+        &asset,
+        &convert_to_seconds(600_000),
+        &convert_to_seconds(600_000),
+    );
+    assert_eq!(
+        result,
+        Some(OHLCVData {
+            open: normalize_price(300),
+            high: normalize_price(300),
+            low: normalize_price(300),
+            close: normalize_price(300),
+            timestamp_open: convert_to_seconds(600_000),
+            timestamp_close: convert_to_seconds(600_000),
+            record_count: 1,
+        })
+    );
+
+    //all gaps
+    let result = client.ohlcv(
+        &asset,
+        &convert_to_seconds(1_200_000),
+        &convert_to_seconds(1_200_000),
+    );
+    assert_eq!(result, None);
+}
+
+#[test]
+fn price_count_cap_test() {
+    let (env, client, init_data) = init_contract(RESOLUTION, 1);
+
+    env.ledger().set(LedgerInfo {
+        timestamp: convert_to_seconds(30 * RESOLUTION as u64),
+        ..env.ledger().get()
+    });
+
+    env.mock_all_auths();
+
+    client.set_period(&(50 * RESOLUTION as u64), &(init_data.version + 1));
+    execute_config_change(&env, &client);
+
+    let updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    for i in 1..=30u64 {
+        client.set_price(&updates, &(i * RESOLUTION as u64));
+    }
+
+    //only the most recent records are counted
+    assert_eq!(client.price_count(&init_data.assets.get_unchecked(0)), 20);
+}
+
+#[test]
+fn price_count_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    assert_eq!(client.price_count(&assets.get_unchecked(1)), 0);
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+
+    let mut updates = get_updates(&env, &assets, normalize_price(200));
+    updates.set(2, 0);
+    client.set_price(&updates, &900_000);
+
+    assert_eq!(client.price_count(&assets.get_unchecked(1)), 2);
+    assert_eq!(client.price_count(&assets.get_unchecked(2)), 1);
+    assert_eq!(
+        client.x_price_count(&assets.get_unchecked(1), &assets.get_unchecked(2)),
+        1
+    );
+    assert_eq!(
+        client.price_count(&Asset::Other(Symbol::new(&env, "NonRegisteredAsset"))),
+        0
+    );
+
+    //move the ledger beyond the retention period
+    env.ledger().set(LedgerInfo {
+        timestamp: convert_to_seconds(900_000 + init_data.period),
+        ..env.ledger().get()
+    });
+
+    assert_eq!(client.price_count(&assets.get_unchecked(1)), 0);
+    assert_eq!(
+        client.x_price_count(&assets.get_unchecked(1), &assets.get_unchecked(2)),
+        0
+    );
+}
+
+#[test]
+fn asset_price_exists_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    let mut updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    updates.set(2, 0);
+    client.set_price(&updates, &300_000);
+
+    env.as_contract(&client.address, || {
+        for asset in 0..init_data.assets.len() {
+            for timestamp in [0, 300_000, 600_000] {
+                let asset = AssetId(asset as u8);
+                assert_eq!(
+                    env.asset_price_exists(asset, timestamp),
+                    env.get_price(asset, timestamp).is_some()
+                );
+            }
+        }
+        assert!(env.asset_price_exists(AssetId(1), 300_000));
+        assert!(!env.asset_price_exists(AssetId(2), 300_000));
+    });
+}
+
+#[test]
+fn last_n_timestamps_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    assert_eq!(client.last_n_timestamps(&10), Vec::new(&env));
+
+    let updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+    client.set_price(&updates, &900_000);
+
+    assert_eq!(
+        client.last_n_timestamps(&10),
+        Vec::from_array(&env, [900, 300])
+    );
+    assert_eq!(client.last_n_timestamps(&1), Vec::from_array(&env, [900]));
+    //the gap at 600 is counted as an inspected step
+    assert_eq!(client.last_n_timestamps(&2), Vec::from_array(&env, [900]));
+
+    //the timestamps without the first asset price are skipped
+    let mut updates = updates;
+    updates.set(0, 0);
+    env.ledger().set(LedgerInfo {
+        timestamp: 1_200,
+        ..env.ledger().get()
+    });
+    client.set_price(&updates, &1_200_000);
+    assert_eq!(
+        client.last_n_timestamps(&10),
+        Vec::from_array(&env, [900, 300])
+    );
+}
+
+#[test]
+fn has_prices_after_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    assert!(!client.has_prices_after(&assets.get_unchecked(1), &0));
+
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.set(2, 0);
+    client.set_price(&updates, &600_000);
+
+    assert!(client.has_prices_after(&assets.get_unchecked(1), &300));
+    assert!(!client.has_prices_after(&assets.get_unchecked(1), &600));
+    //no price for the asset in the last update
+    assert!(!client.has_prices_after(&assets.get_unchecked(2), &300));
+    assert!(!client.has_prices_after(&Asset::Other(Symbol::new(&env, "NonRegisteredAsset")), &300));
+}
+
+#[test]
+fn prune_prices_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    for timestamp in [300_000, 600_000, 900_000].iter() {
+        let updates = get_updates(&env, &assets, normalize_price(100));
+        client.set_price(&updates, timestamp);
+    }
+
+    let result = client.prune_prices(&assets.get_unchecked(1), &convert_to_seconds(900_000));
+    assert_eq!(
+        result,
+        PruneResult {
+            assets_pruned: 1,
+            records_pruned: 2
+        }
+    );
+    assert_eq!(client.price(&assets.get_unchecked(1), &300), None);
+    assert_eq!(client.price(&assets.get_unchecked(1), &600), None);
+    assert_ne!(client.price(&assets.get_unchecked(1), &900), None);
+
+    //nothing left to prune for the asset
+    let result = client.prune_prices(&assets.get_unchecked(1), &convert_to_seconds(900_000));
+    assert_eq!(
+        result,
+        PruneResult {
+            assets_pruned: 0,
+            records_pruned: 0
+        }
+    );
+
+    //the assets are processed in pages of 5
+    let before_ts = convert_to_seconds(900_000);
+    let result = client.prune_all_prices(&before_ts, &0, &10);
+    assert_eq!(
+        result,
+        PruneResult {
+            assets_pruned: 4,
+            records_pruned: 4 * 2
+        }
+    );
+    assert_ne!(client.price(&assets.get_unchecked(5), &600), None);
+
+    let result = client.prune_all_prices(&before_ts, &5, &5);
+    assert_eq!(
+        result,
+        PruneResult {
+            assets_pruned: 5,
+            records_pruned: 5 * 2
+        }
+    );
+    assert_eq!(client.price(&assets.get_unchecked(0), &600), None);
+    assert_ne!(client.lastprice(&assets.get_unchecked(0)), None);
+}
+
+#[test]
+fn bump_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
+
+    //the record lives for the retention period
+    let ledgers_to_live = (convert_to_seconds(init_data.period) / 5 + 1) as u32;
+
+    //move the ledger close to the record expiration
+    let sequence_number = env.ledger().sequence() + ledgers_to_live - 1;
+    env.ledger().set(LedgerInfo {
+        sequence_number,
+        ..env.ledger().get()
+    });
+
+    client.bump_price(&asset, &convert_to_seconds(600_000), &10_000);
+
+    //move the ledger beyond the original expiration
+    env.ledger().set(LedgerInfo {
+        sequence_number: sequence_number + 5_000,
+        ..env.ledger().get()
+    });
+
+    assert_eq!(
+        client.price(&asset, &convert_to_seconds(600_000)),
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+}
+
+#[test]
+#[should_panic]
+fn bump_missing_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.bump_price(
+        &init_data.assets.get_unchecked(1),
+        &convert_to_seconds(600_000),
+        &10_000,
+    );
+}
+
+#[test]
+fn three_way_price_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.set(1, normalize_price(400));
+    updates.set(2, normalize_price(200));
+    client.set_price(&updates, &600_000);
+
+    let (a, bridge, c) = (
+        assets.get_unchecked(1),
+        assets.get_unchecked(2),
+        assets.get_unchecked(3),
+    );
+    let expected = Some(PriceData {
+        price: normalize_price(4),
+        timestamp: convert_to_seconds(600_000),
+    });
+
+    let result = client.three_way_price(&a, &bridge, &c, &convert_to_seconds(600_000));
+    assert_eq!(result, expected);
+    assert_eq!(result, client.x_price(&a, &c, &convert_to_seconds(600_000)));
+
+    let result = client.three_way_price_explicit(&a, &bridge, &c, &convert_to_seconds(600_000));
+    assert_eq!(result, expected);
+
+    //unknown bridge asset
+    let result = client.three_way_price_explicit(
+        &a,
+        &Asset::Other(Symbol::new(&env, "NonRegisteredAsset")),
+        &c,
+        &convert_to_seconds(600_000),
+    );
+    assert_eq!(result, None);
+}
+
+#[test]
+fn is_supported_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let unknown_asset = Asset::Other(Symbol::new(&env, "NonRegisteredAsset"));
+
+    assert!(client.is_asset_supported(&assets.get_unchecked(1)));
+    assert!(!client.is_asset_supported(&unknown_asset));
+
+    assert!(client.is_pair_supported(&assets.get_unchecked(1), &assets.get_unchecked(2)));
+    assert!(!client.is_pair_supported(&assets.get_unchecked(1), &unknown_asset));
+    assert!(!client.is_pair_supported(&unknown_asset, &assets.get_unchecked(1)));
+}
+
+#[test]
+fn get_supported_pairs_test() {
+    let (env, client, init_data) = init_contract(RESOLUTION, 3);
+
+    let assets = init_data.assets;
+    let (a, b, c) = (
+        assets.get_unchecked(0),
+        assets.get_unchecked(1),
+        assets.get_unchecked(2),
+    );
+
+    let result = client.get_supported_pairs(&0, &200);
+    assert_eq!(
+        result,
+        Vec::from_array(
+            &env,
+            [
+                (a.clone(), b.clone()),
+                (a.clone(), c.clone()),
+                (b.clone(), a.clone()),
+                (b.clone(), c.clone()),
+                (c.clone(), a.clone()),
+                (c.clone(), b.clone()),
+            ]
+        )
+    );
+
+    //pagination
+    let result = client.get_supported_pairs(&3, &2);
+    assert_eq!(
+        result,
+        Vec::from_array(&env, [(b.clone(), c.clone()), (c.clone(), a.clone())])
+    );
+
+    //out of range
+    let result = client.get_supported_pairs(&6, &200);
+    assert!(result.is_empty());
+}
+
+#[test]
+fn prices_with_gaps_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+
+    //gap at 600_000
+
+    let updates = get_updates(&env, &assets, normalize_price(200));
+    client.set_price(&updates, &900_000);
+
+    let result = client.prices_with_gaps(&asset, &4);
+    assert_eq!(
+        result,
+        Some(Vec::from_array(
+            &env,
+            [
+                Some(PriceData {
+                    price: normalize_price(200),
+                    timestamp: convert_to_seconds(900_000)
+                }),
+                None,
+                Some(PriceData {
+                    price: normalize_price(100),
+                    timestamp: convert_to_seconds(300_000)
+                }),
+                None
+            ]
+        ))
+    );
+
+    //unknown asset
+    let result =
+        client.prices_with_gaps(&Asset::Other(Symbol::new(&env, "NonRegisteredAsset")), &4);
+    assert_eq!(result, None);
+}
+
+#[test]
+fn lastprice_before_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+    let asset = assets.get_unchecked(1);
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+
+    //gap at 600_000
 
-    let timestamp = 900_000;
     let updates = get_updates(&env, &assets, normalize_price(200));
+    client.set_price(&updates, &900_000);
+
+    //the gap is skipped
+    let result = client.lastprice_before(&asset, &convert_to_seconds(899_000));
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(300_000)
+        })
+    );
+
+    let result = client.lastprice_before(&asset, &convert_to_seconds(900_000));
+    assert_eq!(
+        result,
+        Some(PriceData {
+            price: normalize_price(200),
+            timestamp: convert_to_seconds(900_000)
+        })
+    );
+
+    //the walk starts from the last update
+    let result = client.lastprice_before(&asset, &convert_to_seconds(900_000 * 100));
+    assert_eq!(result.unwrap().timestamp, convert_to_seconds(900_000));
+
+    //no records before the first price
+    let result = client.lastprice_before(&asset, &convert_to_seconds(299_000));
+    assert_eq!(result, None);
+}
+
+#[test]
+fn events_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets.clone();
+
+    //config and asset added events
+    let events = env.events().all();
+    assert_eq!(events.len(), assets.len() + 1);
+    let (contract, topics, data) = events.get_unchecked(0);
+    assert_eq!(contract, client.address);
+    assert_eq!(
+        topics,
+        (symbol_short!("oracle"), Symbol::new(&env, "asset_added")).into_val(&env)
+    );
+    let data: (Asset, u32) = data.into_val(&env);
+    assert_eq!(data, (assets.get_unchecked(0), 0));
+
+    let (_, topics, data) = events.last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("oracle"), Symbol::new(&env, "config_updated")).into_val(&env)
+    );
+    let data: ConfigData = data.into_val(&env);
+    assert_eq!(data, init_data);
+
+    env.mock_all_auths();
+
+    //price update events
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.set(1, 0);
+    let events_count = env.events().all().len();
+    client.set_price(&updates, &600_000);
+
+    let events = env.events().all().slice(events_count..);
+    assert_eq!(events.len(), assets.len() - 1);
+    let (_, topics, data) = events.get_unchecked(1);
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("oracle"),
+            Symbol::new(&env, "price_update"),
+            2u32
+        )
+            .into_val(&env)
+    );
+    let data: (i128, u64) = data.into_val(&env);
+    assert_eq!(data, (normalize_price(100), convert_to_seconds(600_000)));
+
+    //period changed event
+    client.set_period(&1_000_000, &(init_data.version + 1));
+    execute_config_change(&env, &client);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("oracle"), Symbol::new(&env, "period_changed")).into_val(&env)
+    );
+    let data: u64 = data.into_val(&env);
+    assert_eq!(data, 1_000_000);
+}
+
+#[test]
+fn price_rejected_event_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    client.set_price_bounds(
+        &assets.get_unchecked(1),
+        &normalize_price(100),
+        &normalize_price(200),
+    );
+
+    //successful update produces price update event
+    let updates = Vec::from_array(&env, [normalize_price(150), normalize_price(150)]);
+    let events_count = env.events().all().len();
+    client.set_price(&updates, &300_000);
+
+    let events = env.events().all().slice(events_count..);
+    assert_eq!(events.len(), 2);
+    let (_, topics, _) = events.get_unchecked(1);
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("oracle"),
+            Symbol::new(&env, "price_update"),
+            1u32
+        )
+            .into_val(&env)
+    );
+
+    //rejected update produces exactly one rejected event
+    let updates = Vec::from_array(&env, [normalize_price(150), normalize_price(300)]);
+    let events_count = env.events().all().len();
+    client.set_price(&updates, &600_000);
+
+    let events = env.events().all().slice(events_count..);
+    assert_eq!(events.len(), 2);
+    let (_, topics, data) = events.get_unchecked(1);
+    assert_eq!(
+        topics,
+        (symbol_short!("oracle"), Symbol::new(&env, "rejected"), 1u32).into_val(&env)
+    );
+    let data: (i128, i128, u32) = data.into_val(&env);
+    assert_eq!(data, (normalize_price(300), normalize_price(150), 2));
+}
+
+#[test]
+fn storage_stats_test() {
+    let (env, client, init_data) = init_contract(RESOLUTION, 3);
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+
+    let mut updates = get_updates(&env, &assets, normalize_price(200));
+    updates.set(1, 0);
+    client.set_price(&updates, &600_000);
+
+    let result = client.storage_stats(&0, &10);
+    assert_eq!(
+        result,
+        Vec::from_array(
+            &env,
+            [
+                (assets.get_unchecked(0), 2),
+                (assets.get_unchecked(1), 1),
+                (assets.get_unchecked(2), 2)
+            ]
+        )
+    );
+
+    //paging
+    assert_eq!(
+        client.storage_stats(&1, &1),
+        Vec::from_array(&env, [(assets.get_unchecked(1), 1)])
+    );
+    assert_eq!(client.storage_stats(&3, &10), Vec::new(&env));
+}
+
+#[test]
+fn oracle_info_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
+
+    let result = client.oracle_info();
+    assert_eq!(
+        result,
+        OracleInfo {
+            base: init_data.base_asset,
+            decimals: DECIMALS,
+            resolution: RESOLUTION / 1000,
+            period: Some(convert_to_seconds(init_data.period)),
+            asset_count: init_data.assets.len(),
+            last_timestamp: convert_to_seconds(600_000),
+            version: client.version(),
+        }
+    );
+}
+
+#[test]
+fn validate_config_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let result = client.validate_config(&init_data);
+    assert!(result.is_empty());
+
+    let mut config = init_data.clone();
+    config.resolution = 300_001;
+    config.decimals = 0;
+    config.period = 300_001;
+    config.base_asset = config.assets.get_unchecked(0);
+    let result = client.validate_config(&config);
+    assert_eq!(
+        result,
+        Vec::from_array(
+            &env,
+            [
+                Error::InvalidResolution,
+                Error::InvalidDecimalsValue,
+                Error::RetentionPeriodTooShort,
+                Error::InvalidBaseAsset
+            ]
+        )
+    );
+
+    let mut config = init_data;
+    config.resolution = 0;
+    config.decimals = 19;
+    let result = client.validate_config(&config);
+    assert_eq!(
+        result,
+        Vec::from_array(
+            &env,
+            [Error::InvalidResolution, Error::InvalidDecimalsValue]
+        )
+    );
+}
+
+fn config_with_invalid_value_test(update_fn: impl Fn(&mut ConfigData)) {
+    let env = Env::default();
+
+    let contract_id = env.register_contract(None, PriceOracleContract);
+    let client = PriceOracleContractClient::new(&env, &contract_id);
+
+    let mut config = ConfigData {
+        admin: Address::generate(&env),
+        period: (100 * RESOLUTION).into(),
+        assets: generate_assets(&env, 10, 0),
+        base_asset: Asset::Stellar(Address::generate(&env)),
+        decimals: DECIMALS,
+        resolution: RESOLUTION,
+        version: 1,
+    };
+    update_fn(&mut config);
+
+    env.mock_all_auths();
+
+    client.config(&config);
+}
+
+#[test]
+#[should_panic]
+fn config_zero_resolution_test() {
+    config_with_invalid_value_test(|config| config.resolution = 0);
+}
+
+#[test]
+#[should_panic]
+fn config_fractional_resolution_test() {
+    config_with_invalid_value_test(|config| config.resolution = 300_500);
+}
+
+#[test]
+#[should_panic]
+fn config_zero_decimals_test() {
+    config_with_invalid_value_test(|config| config.decimals = 0);
+}
+
+#[test]
+#[should_panic]
+fn config_too_many_decimals_test() {
+    config_with_invalid_value_test(|config| config.decimals = 19);
+}
+
+#[test]
+#[should_panic]
+fn config_short_period_test() {
+    config_with_invalid_value_test(|config| config.period = RESOLUTION.into());
+}
+
+#[test]
+#[should_panic]
+fn set_short_period_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.set_period(&RESOLUTION.into(), &(init_data.version + 1));
+}
+
+#[test]
+fn config_version_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    assert_eq!(client.config_version(), init_data.version);
+
+    env.mock_all_auths();
+
+    client.set_period(&1_000_000, &(init_data.version + 1));
+    assert_eq!(client.config_version(), init_data.version + 1);
+
+    client.add_assets(
+        &generate_assets(&env, 1, init_data.assets.len()),
+        &(init_data.version + 5),
+    );
+    assert_eq!(client.config_version(), init_data.version + 5);
+}
+
+#[test]
+#[should_panic]
+fn stale_config_version_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.set_period(&1_000_000, &(init_data.version + 1));
+    //replay the same version
+    client.set_period(&2_000_000, &(init_data.version + 1));
+}
+
+#[test]
+fn set_price_named_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let updates = Vec::from_array(
+        &env,
+        [
+            PriceUpdateItem {
+                asset: assets.get_unchecked(3),
+                price: normalize_price(300),
+            },
+            PriceUpdateItem {
+                asset: Asset::Other(Symbol::new(&env, "NonRegisteredAsset")),
+                price: normalize_price(200),
+            },
+            PriceUpdateItem {
+                asset: assets.get_unchecked(1),
+                price: normalize_price(100),
+            },
+        ],
+    );
+    client.set_price_named(&updates, &600_000);
+
+    assert_eq!(
+        client.lastprice(&assets.get_unchecked(1)),
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+    assert_eq!(
+        client.lastprice(&assets.get_unchecked(3)),
+        Some(PriceData {
+            price: normalize_price(300),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+    //partial update
+    assert_eq!(client.lastprice(&assets.get_unchecked(2)), None);
+    assert_eq!(client.last_timestamp(), convert_to_seconds(600_000));
+}
+
+fn init_contract_with_max_assets<'a>() -> (Env, PriceOracleContractClient<'a>, Vec<Asset>) {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    let assets = generate_assets(
+        &env,
+        255 - init_data.assets.len() as usize,
+        init_data.assets.len(),
+    );
+    client.add_assets(&assets, &(init_data.version + 1));
+
+    let assets = client.assets();
+    assert_eq!(assets.len(), 255);
+
+    (env, client, assets)
+}
+
+#[test]
+fn max_assets_price_update_test() {
+    let (env, client, assets) = init_contract_with_max_assets();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
+
+    //the last asset is updated
+    assert_eq!(
+        client.lastprice(&assets.last().unwrap()),
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(600_000)
+        })
+    );
+}
+
+#[test]
+fn max_assets_stress_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    env.budget().reset_unlimited();
+
+    //register assets in batches
+    let mut version = init_data.version;
+    let mut assets_count = init_data.assets.len();
+    while assets_count < 255 {
+        let batch_size = (255 - assets_count).min(50);
+        version += 1;
+        client.add_assets(
+            &generate_assets(&env, batch_size as usize, assets_count),
+            &version,
+        );
+        assets_count += batch_size;
+    }
+
+    let assets = client.assets();
+    assert_eq!(assets.len(), 255);
+
+    let mut updates = Vec::new(&env);
+    for i in 0..assets.len() {
+        updates.push_back(normalize_price(i as i128 + 1));
+    }
+    client.set_price(&updates, &600_000);
+
+    for i in [0, 127, 254] {
+        assert_eq!(
+            client.lastprice(&assets.get_unchecked(i)),
+            Some(PriceData {
+                price: normalize_price(i as i128 + 1),
+                timestamp: convert_to_seconds(600_000)
+            })
+        );
+    }
+}
+
+#[test]
+#[should_panic]
+fn max_assets_price_update_overflow_test() {
+    let (env, client, assets) = init_contract_with_max_assets();
+
+    let mut updates = get_updates(&env, &assets, normalize_price(100));
+    updates.push_back(normalize_price(100));
+    client.set_price(&updates, &600_000);
+}
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+#[test]
+fn asset_count_test() {
+    let (env, client, init_data) = init_contract_with_admin();
 
-    let result = client.twap(&assets.get_unchecked(1), &2);
+    env.as_contract(&client.address, || {
+        assert_eq!(env.get_asset_count(), init_data.assets.len());
+    });
 
-    assert_ne!(result, None);
-    assert_eq!(result.unwrap(), normalize_price(150));
+    env.mock_all_auths();
+
+    client.add_assets(
+        &generate_assets(&env, 3, init_data.assets.len()),
+        &(init_data.version + 1),
+    );
+
+    env.as_contract(&client.address, || {
+        assert_eq!(env.get_asset_count(), init_data.assets.len() + 3);
+        assert_eq!(env.get_asset_count(), env.get_assets().len());
+    });
 }
 
 #[test]
-fn x_twap_test() {
+fn storage_layout_version_test() {
+    let (_env, client, _init_data) = init_contract_with_admin();
+
+    assert_eq!(client.storage_layout_version(), STORAGE_LAYOUT_VERSION);
+}
+
+#[test]
+fn migrate_v1_to_v2_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
-    let assets = init_data.assets;
+    //simulate the storage written by the version 1 contract
+    env.as_contract(&client.address, || {
+        env.storage().instance().remove(&"asset_count");
+        env.storage().instance().remove(&"layout_version");
+        assert!(!env.storage().instance().has(&"asset_count"));
+    });
+    assert_eq!(client.storage_layout_version(), 1);
 
-    //set prices for assets
-    let timestamp = 600_000;
-    let updates = get_updates(&env, &assets, normalize_price(100));
+    //the queries don't write the missing counter
+    env.as_contract(&client.address, || {
+        assert_eq!(env.get_asset_count(), 0);
+        assert!(!env.storage().instance().has(&"asset_count"));
+    });
 
     env.mock_all_auths();
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    client.migrate_v1_to_v2();
+    assert_eq!(client.storage_layout_version(), 2);
+    env.as_contract(&client.address, || {
+        assert_eq!(
+            env.storage().instance().get::<_, u32>(&"asset_count"),
+            Some(init_data.assets.len())
+        );
+    });
 
-    let timestamp = 900_000;
-    let updates = get_updates(&env, &assets, normalize_price(200));
+    //the second call is a no-op
+    client.migrate_v1_to_v2();
+    assert_eq!(client.storage_layout_version(), 2);
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    let updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
+    assert_eq!(
+        client.lastprice(&init_data.assets.get_unchecked(1)),
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: 600
+        })
+    );
+    assert_eq!(client.assets().len(), init_data.assets.len());
+}
 
-    let result = client.x_twap(&assets.get_unchecked(1), &assets.get_unchecked(2), &2);
+// Builds the minimal WASM module accepted by the host: the module header and the env meta section
+// with the protocol 20 interface version.
+fn minimal_contract_wasm() -> std::vec::Vec<u8> {
+    let name = b"contractenvmetav0";
+    //SCEnvMetaEntry::InterfaceVersion XDR
+    let mut payload = 0u32.to_be_bytes().to_vec();
+    payload.extend_from_slice(&(20u64 << 32).to_be_bytes());
+    let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+    wasm.push(0); //custom section id
+    wasm.push((1 + name.len() + payload.len()) as u8);
+    wasm.push(name.len() as u8);
+    wasm.extend_from_slice(name);
+    wasm.extend_from_slice(&payload);
+    wasm
+}
 
-    assert_ne!(result, None);
-    assert_eq!(result.unwrap(), normalize_price(1));
+#[test]
+fn wasm_hash_test() {
+    let (env, client, _init_data) = init_contract_with_admin();
+
+    assert_eq!(client.wasm_hash(), None);
+
+    env.mock_all_auths();
+
+    let wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(minimal_contract_wasm().as_slice());
+    client.update_contract(&wasm_hash);
+
+    env.as_contract(&client.address, || {
+        assert_eq!(env.get_wasm_hash(), Some(wasm_hash));
+        assert_eq!(
+            env.get_last_upgrade_timestamp(),
+            Some(env.ledger().timestamp())
+        );
+    });
+}
+
+#[test]
+fn validate_upgrade_test() {
+    let (env, client, _init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    let wasm_hash = BytesN::from_array(&env, &[1; 32]);
+    assert!(client.validate_upgrade(&wasm_hash));
+
+    //simulate the recent update, no delay by default
+    env.as_contract(&client.address, || {
+        env.set_last_upgrade_timestamp(env.ledger().timestamp());
+    });
+    assert_eq!(client.upgrade_delay(), 0);
+    assert!(client.validate_upgrade(&wasm_hash));
+
+    let delay = 86_400;
+    client.set_upgrade_delay(&delay);
+    assert_eq!(client.upgrade_delay(), delay);
+    assert!(!client.validate_upgrade(&wasm_hash));
+
+    let ledger_info = env.ledger().get();
+    env.ledger().set(LedgerInfo {
+        timestamp: ledger_info.timestamp + delay,
+        ..ledger_info
+    });
+    assert!(client.validate_upgrade(&wasm_hash));
+
+    //the delay can be lifted for emergency updates
+    env.as_contract(&client.address, || {
+        env.set_last_upgrade_timestamp(env.ledger().timestamp());
+    });
+    assert!(!client.validate_upgrade(&wasm_hash));
+    client.set_upgrade_delay(&0);
+    assert!(client.validate_upgrade(&wasm_hash));
 }
 
 #[test]
 #[should_panic]
-fn x_twap_with_gap_test() {
+fn update_contract_too_early_test() {
+    let (env, client, _init_data) = init_contract_with_admin();
+
+    env.mock_all_auths();
+
+    client.set_upgrade_delay(&86_400);
+    env.as_contract(&client.address, || {
+        env.set_last_upgrade_timestamp(env.ledger().timestamp());
+    });
+    let wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(minimal_contract_wasm().as_slice());
+    client.update_contract(&wasm_hash);
+}
+
+#[test]
+#[should_panic]
+fn set_price_storage_migration_required_test() {
     let (env, client, init_data) = init_contract_with_admin();
 
-    let assets = init_data.assets;
+    env.as_contract(&client.address, || {
+        env.set_layout_version(STORAGE_LAYOUT_VERSION + 1);
+    });
 
-    //set prices for assets with gap
-    let timestamp = 300_000;
-    let updates = get_updates(&env, &assets, normalize_price(100));
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    client.set_price(&updates, &600_000);
+}
+
+#[test]
+#[should_panic]
+fn set_price_more_updates_than_assets_test() {
+    let (env, client, init_data) = init_contract_with_admin();
 
     env.mock_all_auths();
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    let mut updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    updates.push_back(normalize_price(100));
+    client.set_price(&updates, &600_000);
+}
 
-    let timestamp = 900_000;
-    let updates = get_updates(&env, &assets, normalize_price(200));
+#[test]
+fn get_all_prices_at_test() {
+    let (env, client, init_data) = init_contract_with_admin();
 
-    //set prices for assets
-    client.set_price(&updates, &timestamp);
+    env.mock_all_auths();
 
-    let result = client.x_twap(&assets.get_unchecked(1), &assets.get_unchecked(2), &3);
+    let mut updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    updates.set(1, 0);
+    client.set_price(&updates, &600_000);
 
-    assert_ne!(result, None);
-    assert_eq!(result.unwrap(), normalize_price(1));
+    env.as_contract(&client.address, || {
+        let prices = env.get_all_prices_at(600_000);
+        assert_eq!(prices.len(), env.get_asset_count());
+        assert_eq!(prices.get_unchecked(0), Some(normalize_price(100)));
+        assert_eq!(prices.get_unchecked(1), None);
+
+        //no prices at the timestamp
+        let prices = env.get_all_prices_at(300_000);
+        assert_eq!(prices.len(), env.get_asset_count());
+        assert!(prices.iter().all(|price| price.is_none()));
+    });
 }
 
 #[test]
-fn get_non_registered_asset_price_test() {
-    let (env, client, config_data) = init_contract_with_admin();
+fn all_prices_at_test() {
+    let (env, client, init_data) = init_contract_with_admin();
 
-    //try to get price for unknown Stellar asset
-    let mut result = client.lastprice(&Asset::Stellar(Address::generate(&env)));
-    assert_eq!(result, None);
+    env.mock_all_auths();
 
-    //try to get price for unknown Other asset
-    result = client.lastprice(&Asset::Other(Symbol::new(&env, "NonRegisteredAsset")));
-    assert_eq!(result, None);
+    let mut updates = get_updates(&env, &init_data.assets, normalize_price(100));
+    updates.set(1, 0);
+    client.set_price(&updates, &600_000);
 
-    //try to get price for unknown base asset
-    result = client.x_last_price(
-        &Asset::Stellar(Address::generate(&env)),
-        &config_data.assets.get_unchecked(1),
-    );
-    assert_eq!(result, None);
+    let prices = client.all_prices_at(&convert_to_seconds(600_000));
+    assert_eq!(prices.len(), init_data.assets.len());
+    assert_eq!(prices.get_unchecked(0), Some(normalize_price(100)));
+    assert_eq!(prices.get_unchecked(1), None);
 
-    //try to get price for unknown quote asset
-    result = client.x_last_price(
-        &config_data.assets.get_unchecked(1),
-        &Asset::Stellar(Address::generate(&env)),
+    //the timestamp is normalized to the resolution
+    assert_eq!(
+        client.all_prices_at(&(convert_to_seconds(600_000) + 10)),
+        prices
     );
-    assert_eq!(result, None);
+}
 
-    //try to get price for both unknown assets
-    result = client.x_last_price(
-        &Asset::Stellar(Address::generate(&env)),
-        &Asset::Other(Symbol::new(&env, "NonRegisteredAsset")),
+#[test]
+fn refresh_retention_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    let assets = init_data.assets;
+
+    env.mock_all_auths();
+
+    let updates = get_updates(&env, &assets, normalize_price(100));
+    client.set_price(&updates, &300_000);
+    let mut updates = get_updates(&env, &assets, normalize_price(200));
+    updates.set(1, 0);
+    client.set_price(&updates, &600_000);
+
+    //increase the retention period
+    let period = init_data.period * 2;
+    client.set_period(&period, &(init_data.version + 1));
+    execute_config_change(&env, &client);
+
+    //the assets are processed in pages of 5
+    let from_ts = convert_to_seconds(600_000);
+    assert_eq!(client.refresh_retention(&from_ts, &0, &10), 5 * 2 - 1);
+    assert_eq!(client.refresh_retention(&from_ts, &5, &5), 5 * 2);
+    assert_eq!(client.refresh_retention(&from_ts, &10, &5), 0);
+
+    //the page doesn't reach the records 20 steps back
+    let page_start = convert_to_seconds(600_000 + 20 * RESOLUTION as u64);
+    assert_eq!(client.refresh_retention(&page_start, &0, &5), 0);
+
+    //the timestamp conversion doesn't overflow
+    assert_eq!(client.refresh_retention(&u64::MAX, &0, &5), 0);
+
+    //move the ledger beyond the old retention period
+    let ledgers_to_live = (convert_to_seconds(init_data.period) / 5 + 1) as u32;
+    env.ledger().set(LedgerInfo {
+        sequence_number: env.ledger().sequence() + ledgers_to_live + 1,
+        timestamp: convert_to_seconds(600_000 + init_data.period),
+        ..env.ledger().get()
+    });
+
+    assert_eq!(
+        client.price(&assets.get_unchecked(1), &convert_to_seconds(300_000)),
+        Some(PriceData {
+            price: normalize_price(100),
+            timestamp: convert_to_seconds(300_000)
+        })
     );
-    assert_eq!(result, None);
 }
 
 #[test]
-fn get_asset_price_for_invalid_timestamp_test() {
-    let (env, client, config_data) = init_contract_with_admin();
+fn price_record_key_round_trip_test() {
+    //simple linear congruential generator to get reproducible pseudo-random values
+    let mut seed: u64 = 0x2545F4914F6CDD1D;
+    let mut next = || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        seed
+    };
 
-    let mut result = client.price(
-        &config_data.assets.get_unchecked(1),
-        &convert_to_seconds(u64::MAX),
+    let mut cases = std::vec![(0, 0), (u64::MAX, u8::MAX), (600_000, 1)];
+    for _ in 0..1000 {
+        cases.push((next(), next() as u8));
+    }
+
+    for (timestamp, asset) in cases {
+        let key = U128Helper::encode_price_record_key(timestamp, asset);
+        assert_eq!(U128Helper::decode_price_record_key(key), (timestamp, asset));
+    }
+}
+
+#[test]
+fn asset_display_test() {
+    let env = Env::default();
+
+    let asset = Asset::Other(Symbol::new(&env, "BTC"));
+    assert_eq!(alloc::format!("{}", asset), "G:BTC");
+
+    let address = Address::generate(&env);
+    let strkey = address.to_string();
+    let mut buf = [0u8; 56];
+    strkey.copy_into_slice(&mut buf[..strkey.len() as usize]);
+    let expected = std::str::from_utf8(&buf[..strkey.len() as usize]).unwrap();
+    assert_eq!(
+        alloc::format!("{}", Asset::Stellar(address)),
+        alloc::format!("S:{}", expected)
     );
-    assert_eq!(result, None);
+}
 
-    //try to get price for unknown asset
-    result = client.lastprice(&Asset::Stellar(Address::generate(&env)));
-    assert_eq!(result, None);
+#[test]
+fn price_record_key_validation_test() {
+    assert!(U128Helper::is_valid_price_key(
+        U128Helper::encode_price_record_key(u64::MAX, u8::MAX).to_u128()
+    ));
+    assert!(U128Helper::is_valid_price_key(
+        U128Helper::encode_price_record_key(600_000, 1).to_u128()
+    ));
+    assert!(U128Helper::is_valid_price_key(0));
+
+    //middle bits are set
+    assert!(!U128Helper::is_valid_price_key(1 << 8));
+    assert!(!U128Helper::is_valid_price_key(1 << 63));
+    assert!(!U128Helper::is_valid_price_key(
+        U128Helper::encode_price_record_key(600_000, 1).to_u128() | 0xFF00
+    ));
 }
 
 #[test]
-fn authorized_test() {
-    let (env, client, config_data) = init_contract_with_admin();
+fn is_within_window_test() {
+    let reference = 900_000;
+    let window = 600_000;
+
+    //exactly at the boundary
+    assert!(!300_000u64.is_within_window(reference, window));
+    //one millisecond inside
+    assert!(300_001u64.is_within_window(reference, window));
+    //one millisecond outside
+    assert!(!299_999u64.is_within_window(reference, window));
+
+    assert!(reference.is_within_window(reference, window));
+    //future timestamp
+    assert!(!900_001u64.is_within_window(reference, window));
+}
 
-    let period: u64 = 100;
-    //set prices for assets
-    client
-        .mock_auths(&[MockAuth {
-            address: &config_data.admin,
-            invoke: &MockAuthInvoke {
-                contract: &client.address,
-                fn_name: "set_period",
-                args: Vec::from_array(&env, [period.clone().try_into_val(&env).unwrap()]),
-                sub_invokes: &[],
-            },
-        }])
-        .set_period(&period);
+#[test]
+fn clamp_to_resolution_test() {
+    let resolution = RESOLUTION as u64;
+
+    assert_eq!(0u64.clamp_to_resolution(resolution), 0);
+    assert_eq!(600_000u64.clamp_to_resolution(0), 0);
+    //exactly at the boundary
+    assert_eq!(600_000u64.clamp_to_resolution(resolution), 600_000);
+    assert_eq!(899_999u64.clamp_to_resolution(resolution), 600_000);
+    assert_eq!(299_999u64.clamp_to_resolution(resolution), 0);
 }
 
 #[test]
-#[should_panic]
-fn unauthorized_test() {
-    let (env, client, _) = init_contract_with_admin();
+fn normalized_timestamp_idempotency_test() {
+    let resolution = RESOLUTION as u64;
 
-    let account = Address::generate(&env);
+    assert_eq!(0u64.get_normalized_timestamp(resolution), 0);
 
-    let period: u64 = 100;
-    //set prices for assets
-    client
-        .mock_auths(&[MockAuth {
-            address: &account,
-            invoke: &MockAuthInvoke {
-                contract: &client.address,
-                fn_name: "set_period",
-                args: Vec::from_array(&env, [period.clone().try_into_val(&env).unwrap()]),
-                sub_invokes: &[],
-            },
-        }])
-        .set_period(&period);
+    for timestamp in [0, 1, 299_999, 300_000, 300_001, u64::MAX / 2] {
+        let normalized = timestamp.get_normalized_timestamp(resolution);
+        assert_eq!(normalized.get_normalized_timestamp(resolution), normalized);
+    }
 }
 
 #[test]
-fn div_tests() {
-    let test_cases = [
-        (154467226919499, 133928752749774, 115335373284703),
-        (
-            i128::MAX / 100,
-            231731687303715884105728,
-            734216306110962248249052545,
-        ),
-        (231731687303715884105728, i128::MAX / 100, 13),
-        // -1 expected result for errors
-        (1, 0, -1),
-        (0, 1, -1),
-        (0, 0, -1),
-        (-1, 0, -1),
-        (0, -1, -1),
-        (-1, -1, -1),
+fn weighted_average_tests() {
+    //uniform weights
+    let values = [
+        (normalize_price(100), 1),
+        (normalize_price(200), 1),
+        (normalize_price(300), 1),
     ];
+    assert_eq!(
+        weighted_average(values.iter().copied()),
+        Some(normalize_price(200))
+    );
 
-    for (a, b, expected) in test_cases.iter() {
-        let result = panic::catch_unwind(AssertUnwindSafe(|| a.fixed_div_floor(*b, 14)));
-        if expected == &-1 {
-            assert!(result.is_err());
-        } else {
-            assert_eq!(result.unwrap(), *expected);
-        }
-    }
+    //non-uniform weights
+    let values = [(normalize_price(100), 7_500), (normalize_price(200), 2_500)];
+    assert_eq!(
+        weighted_average(values.iter().copied()),
+        Some(normalize_price(125))
+    );
+
+    let values = [(10, 1), (11, 2)];
+    assert_eq!(weighted_average(values.iter().copied()), Some(10));
+
+    //large values don't overflow
+    let values = [(i128::MAX / 2, u32::MAX), (i128::MAX / 2, u32::MAX)];
+    assert_eq!(
+        weighted_average(values.iter().copied()),
+        Some(i128::MAX / 2)
+    );
+
+    //empty input and zero weights
+    assert_eq!(weighted_average([].iter().copied()), None);
+    assert_eq!(
+        weighted_average([(normalize_price(100), 0)].iter().copied()),
+        None
+    );
+}
+
+#[test]
+fn oracle_interface_test() {
+    let (env, client, init_data) = init_contract_with_admin();
+
+    env.as_contract(&client.address, || {
+        assert_eq!(
+            <PriceOracleContract as OracleInterface>::base(&env),
+            init_data.base_asset
+        );
+        assert_eq!(
+            <PriceOracleContract as OracleInterface>::decimals(&env),
+            DECIMALS
+        );
+        assert_eq!(
+            <PriceOracleContract as OracleInterface>::assets(&env),
+            init_data.assets
+        );
+        assert_eq!(
+            <PriceOracleContract as OracleInterface>::lastprice(
+                &env,
+                init_data.assets.get_unchecked(1)
+            ),
+            None
+        );
+    });
 }
@@ -0,0 +1,17 @@
+use soroban_sdk::contracttype;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The price data for an asset along with its staleness relative to the current ledger.
+pub struct AggregatedPriceData {
+    // The price in contracts' base asset and decimals.
+    pub price: i128,
+    // The timestamp of the price.
+    pub timestamp: u64,
+    // The age of the price in seconds relative to the ledger timestamp.
+    pub age_seconds: u64,
+    // Whether the price age doesn't exceed the max allowed age.
+    pub is_fresh: bool,
+    // The max allowed price age in seconds.
+    pub max_age_seconds: u64,
+}
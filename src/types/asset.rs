@@ -1,8 +1,41 @@
+use core::fmt;
 use soroban_sdk::{contracttype, Address, Symbol};
 
+// Length of the Stellar strkey (G... account or C... contract address).
+const STRKEY_LENGTH: usize = 56;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Asset {
     Stellar(Address),
     Other(Symbol),
 }
+
+// Formats Stellar assets as `S:{strkey}` and other (symbol) assets as `G:{symbol}`.
+// Symbol contents are not available in wasm builds without the host environment, so they are printed opaquely there.
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Asset::Stellar(address) => {
+                let strkey = address.to_string();
+                let len = strkey.len() as usize;
+                if len > STRKEY_LENGTH {
+                    return Err(fmt::Error);
+                }
+                let mut buf = [0u8; STRKEY_LENGTH];
+                strkey.copy_into_slice(&mut buf[..len]);
+                let strkey = core::str::from_utf8(&buf[..len]).map_err(|_| fmt::Error)?;
+                write!(f, "S:{}", strkey)
+            }
+            Asset::Other(symbol) => {
+                #[cfg(not(target_family = "wasm"))]
+                {
+                    use alloc::string::ToString;
+                    write!(f, "G:{}", symbol.to_string())
+                }
+                #[cfg(target_family = "wasm")]
+                write!(f, "G:{:?}", symbol)
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+// The index of a quoted asset in the contract assets list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AssetId(pub u8);
@@ -0,0 +1,13 @@
+use soroban_sdk::{contracttype, Symbol};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// Human-readable metadata for a quoted asset.
+pub struct AssetMetadata {
+    // The display name of the asset.
+    pub name: Symbol,
+    // The short description of the asset.
+    pub description: Symbol,
+    // The number of decimals to use for the asset instead of the oracle decimals, if any.
+    pub decimals_override: Option<u32>,
+}
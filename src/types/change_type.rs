@@ -0,0 +1,13 @@
+use soroban_sdk::contracttype;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+// The contract setting updated by a time-locked config change. The unit of the new value depends on the setting.
+pub enum ChangeType {
+    // The history retention period (in milliseconds).
+    Period = 1,
+    // The last price grace period (in seconds).
+    GracePeriod = 2,
+    // The config change timelock (in seconds).
+    Timelock = 3,
+}
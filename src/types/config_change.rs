@@ -0,0 +1,13 @@
+use soroban_sdk::contracttype;
+
+use super::change_type::ChangeType;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The time-locked contract config change.
+pub struct ConfigChange {
+    // The setting to update.
+    pub change_type: ChangeType,
+    // The new setting value, in the unit documented for the change type.
+    pub new_value: u64,
+}
@@ -1,7 +1,7 @@
 use soroban_sdk::contracterror;
 
 #[contracterror]
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 // The error codes for the contract.
 pub enum Error {
     // The contract is already initialized.
@@ -20,4 +20,42 @@ pub enum Error {
     InvalidUpdateLength = 6,
     // The assets storage is full
     AssetLimitExceeded = 7,
+    // The price bounds are invalid
+    InvalidPriceBounds = 8,
+    // The price is out of the configured bounds
+    PriceOutOfBounds = 9,
+    // The grace period is invalid
+    InvalidGracePeriod = 10,
+    // The basket weights are invalid
+    InvalidWeights = 11,
+    // The price record is not found
+    PriceNotFound = 12,
+    // The resolution is invalid
+    InvalidResolution = 13,
+    // The decimals value is invalid
+    InvalidDecimalsValue = 14,
+    // The retention period is shorter than two resolution steps
+    RetentionPeriodTooShort = 15,
+    // The base asset is in the list of quoted assets
+    InvalidBaseAsset = 16,
+    // The min quorum value is invalid
+    InvalidQuorum = 17,
+    // The price round with the same id is already open
+    RoundAlreadyExists = 18,
+    // The price round is not found
+    RoundNotFound = 19,
+    // The price round doesn't have enough submissions
+    QuorumNotReached = 20,
+    // The price doesn't fit into the contract's decimals representation
+    PriceConversionOverflow = 21,
+    // The same price snapshot was already submitted for the timestamp
+    DuplicateUpdate = 22,
+    // There is no pending config change
+    ConfigChangeNotFound = 23,
+    // The config change timelock has not expired yet
+    ConfigChangeLocked = 24,
+    // The contract storage layout doesn't match the contract code and has to be migrated
+    StorageMigrationRequired = 25,
+    // The min delay since the last contract update hasn't elapsed yet
+    UpgradeTooEarly = 26,
 }
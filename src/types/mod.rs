@@ -1,5 +1,19 @@
+pub mod aggregated_price_data;
 pub mod asset;
+pub mod asset_id;
+pub mod asset_metadata;
 pub mod asset_type;
+pub mod change_type;
+pub mod config_change;
 pub mod config_data;
 pub mod error;
+pub mod ohlc_data;
+pub mod ohlcv_data;
+pub mod oracle_info;
 pub mod price_data;
+pub mod price_round;
+pub mod price_update_item;
+pub mod price_with_confidence;
+pub mod prune_result;
+pub mod pyth_price_update;
+pub mod uptime_stats;
@@ -0,0 +1,21 @@
+use soroban_sdk::contracttype;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The open/high/low/close prices for an asset over a given time range, along with the number of records found.
+pub struct OHLCVData {
+    // The first price in the range.
+    pub open: i128,
+    // The max price in the range.
+    pub high: i128,
+    // The min price in the range.
+    pub low: i128,
+    // The last price in the range.
+    pub close: i128,
+    // The timestamp of the first price.
+    pub timestamp_open: u64,
+    // The timestamp of the last price.
+    pub timestamp_close: u64,
+    // The number of resolution slots in the range that had a price.
+    pub record_count: u32,
+}
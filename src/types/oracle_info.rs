@@ -0,0 +1,23 @@
+use soroban_sdk::contracttype;
+
+use super::asset::Asset;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The aggregated metadata of the contract.
+pub struct OracleInfo {
+    // The base asset for the prices.
+    pub base: Asset,
+    // The number of decimals for the prices.
+    pub decimals: u32,
+    // The resolution of the prices (in seconds).
+    pub resolution: u32,
+    // The retention period for the prices (in seconds).
+    pub period: Option<u64>,
+    // The number of assets supported by the contract.
+    pub asset_count: u32,
+    // The timestamp of the last price update (in seconds).
+    pub last_timestamp: u64,
+    // The contract protocol version.
+    pub version: u32,
+}
@@ -0,0 +1,13 @@
+use soroban_sdk::{contracttype, Address, Map, Vec};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The price collection round.
+pub struct PriceRound {
+    // The history snapshot timestamp of the round (in milliseconds).
+    pub timestamp: u64,
+    // The submitter that opened the round.
+    pub opened_by: Address,
+    // The price updates contributed by each submitter.
+    pub submissions: Map<Address, Vec<i128>>,
+}
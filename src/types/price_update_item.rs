@@ -0,0 +1,13 @@
+use soroban_sdk::contracttype;
+
+use super::asset::Asset;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The price update for a single asset.
+pub struct PriceUpdateItem {
+    // The asset to update.
+    pub asset: Asset,
+    // The price in contracts' base asset and decimals.
+    pub price: i128,
+}
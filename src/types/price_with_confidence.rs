@@ -0,0 +1,13 @@
+use soroban_sdk::contracttype;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The price data for an asset at a given timestamp along with its confidence interval.
+pub struct PriceWithConfidence {
+    // The price in contracts' base asset and decimals.
+    pub price: i128,
+    // The confidence interval of the price in contracts' decimals.
+    pub confidence: i128,
+    // The timestamp of the price.
+    pub timestamp: u64,
+}
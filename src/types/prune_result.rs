@@ -0,0 +1,11 @@
+use soroban_sdk::contracttype;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The result of the price records cleanup.
+pub struct PruneResult {
+    // The number of assets with at least one removed price record.
+    pub assets_pruned: u32,
+    // The total number of removed price records.
+    pub records_pruned: u32,
+}
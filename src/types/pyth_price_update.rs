@@ -0,0 +1,19 @@
+use soroban_sdk::contracttype;
+
+use super::asset::Asset;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+// The price update for a single asset in the Pyth Network format.
+pub struct PythPriceUpdate {
+    // The asset to update.
+    pub asset: Asset,
+    // The price mantissa.
+    pub price: i64,
+    // The confidence interval mantissa.
+    pub confidence: u64,
+    // The decimal exponent of the price and confidence.
+    pub exponent: i32,
+    // The Pyth publish timestamp (in seconds).
+    pub publish_time: u64,
+}
@@ -0,0 +1,13 @@
+use soroban_sdk::contracttype;
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+// The price update regularity stats.
+pub struct UptimeStats {
+    // The total number of recorded price snapshots.
+    pub total_updates: u64,
+    // The timestamp of the last recorded price snapshot (in seconds).
+    pub last_update_timestamp: u64,
+    // The number of resolution intervals missed since the last regular update.
+    pub consecutive_missed_intervals: u32,
+}